@@ -0,0 +1,41 @@
+#!/usr/bin/env bash
+# naming.sh — canonical variant naming shared by the Ollama stack (source this file)
+# Variant names are <ALIAS_PREFIX><base_alias>[-<quant>]<ALIAS_SUFFIX>-<gpu label>-ng<N>;
+# benchmark.sh, bake-modelfile.sh, variants-stale.sh and store-fixtures.sh must
+# all derive them the same way, so the helpers live here only.
+# Golden cases: tests/naming.test.sh
+
+normalize_gpu_label(){
+  # "NVIDIA GeForce RTX 5090" -> nvidia-5090 ; "NVIDIA GeForce RTX 3090 Ti" -> nvidia-3090ti
+  # Already-normalized labels ("nvidia-5090") are returned unchanged
+  local s
+  s="$(echo "$1" | tr '[:upper:]' '[:lower:]')"
+  s="${s#nvidia-}"
+  s="${s//nvidia /}"
+  s="${s//geforce /}"
+  s="${s//rtx /}"
+  s="${s// /}"
+  echo "nvidia-${s}"
+}
+
+base_alias(){ # "llama4:16x17b" -> "llama4-16x17b" with compact suffixes
+  local s
+  s="$(echo "$1" | sed -E 's#[/:]+#-#g')"
+  # Compact common tokens: it->i, fp16->f16, bf16->b16; a trailing -it/-i is dropped
+  s="${s//-it-/-i-}"
+  s="${s%-it}"; s="${s%-i}"
+  s="${s//-it/-i}"
+  s="${s//-fp16/-f16}"
+  s="${s//-bf16/-b16}"
+  echo "$s"
+}
+
+quant_tag(){ # "q4_K_M" -> "-q4_K_M"; "" -> "" (create-time quantization is part of the name)
+  [ -n "$1" ] || return 0
+  echo "-${1//[^[:alnum:]_.]/-}"
+}
+
+# <ALIAS_PREFIX><base_alias>[-<quant>]<ALIAS_SUFFIX>-<gpu label>-ng<N>
+variant_name(){ # base gpu_label num_gpu [quantize]
+  echo "${ALIAS_PREFIX:-}$(base_alias "$1")$(quant_tag "${4:-}")${ALIAS_SUFFIX:-}-${2}-ng${3}"
+}
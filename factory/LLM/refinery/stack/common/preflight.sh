@@ -85,6 +85,12 @@ section_models(){
   if [ -d "$om" ]; then
     local o; o=$(stat -c '%U:%G %a' "$om" 2>/dev/null || true)
     ok "Ollama store : $om ($o)"
+    local stale here
+    here="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+    stale=$("$here/../ollama/variants-stale.sh" --store "$om" --count 2>/dev/null || echo 0)
+    if [ "${stale:-0}" -gt "${STALE_WARN_THRESHOLD:-0}" ]; then
+      warn "Stale variants: $stale baked from an outdated base (see: ./ust.sh ollama variants-stale)"; mark_warn
+    fi
   else
     warn "Ollama store : missing ($om). Will be created on demand."; fi
   if [ -d "$gg" ]; then
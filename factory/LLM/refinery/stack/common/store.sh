@@ -0,0 +1,36 @@
+#!/usr/bin/env bash
+# store.sh — Ollama store paths and log dir shared by the stack scripts (source this file)
+# Model names <-> manifest paths and the writable LOG_DIR fallback live here
+# only, so every script that walks a store agrees on them.
+
+# Writable log dir: LOG_DIR (default /var/log/fuze-stack), else a per-user
+# location when the system one is root-owned
+init_log_dir(){
+  LOG_DIR="${LOG_DIR:-/var/log/fuze-stack}"
+  if ! mkdir -p "$LOG_DIR" 2>/dev/null || [ ! -w "$LOG_DIR" ]; then
+    LOG_DIR="${XDG_STATE_HOME:-$HOME/.local/state}/fuze-stack/logs"
+    mkdir -p "$LOG_DIR" 2>/dev/null || { LOG_DIR="$HOME/.fuze/stack/logs"; mkdir -p "$LOG_DIR"; }
+  fi
+}
+
+with_latest(){ case "$1" in *:*) echo "$1";; *) echo "$1:latest";; esac; }
+
+# "gemma3:4b" -> manifests/registry.ollama.ai/library/gemma3/4b
+# "ns/model:tag" -> manifests/registry.ollama.ai/ns/model/tag
+# "host.tld/ns/model:tag" -> manifests/host.tld/ns/model/tag
+manifest_rel(){
+  local name="$1" repo tag
+  case "$name" in *:*) repo="${name%:*}"; tag="${name##*:}";; *) repo="$name"; tag="latest";; esac
+  case "$repo" in
+    */*/*) echo "manifests/${repo}/${tag}" ;;
+    */*)   echo "manifests/registry.ollama.ai/${repo}/${tag}" ;;
+    *)     echo "manifests/registry.ollama.ai/library/${repo}/${tag}" ;;
+  esac
+}
+
+manifest_name(){ # inverse of manifest_rel (always tagged)
+  local rel="${1#manifests/}" tag repo
+  tag="${rel##*/}"; repo="${rel%/*}"
+  repo="${repo#registry.ollama.ai/}"; repo="${repo#library/}"
+  echo "${repo}:${tag}"
+}
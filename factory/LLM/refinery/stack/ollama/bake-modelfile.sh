@@ -0,0 +1,171 @@
+#!/usr/bin/env bash
+# bake-modelfile.sh
+# Render a Modelfile for a baked variant from ollama/templates/Modelfile.template
+# and derive the canonical variant name: <alias>[-<quant>]-<normalized-gpu>-ng<NUM>
+# (common/naming.sh, as ollama/benchmark.sh, so cleanup-variants.sh matches it).
+# Optionally create the variant on a daemon from that Modelfile (ollama create -f).
+
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+
+###############################################################################
+# Defaults (override via flags)
+###############################################################################
+TEMPLATE="${TEMPLATE:-${SCRIPT_DIR}/templates/Modelfile.template}"
+OUT_DIR="${OUT_DIR:-./modelfiles}"
+HOST="${HOST:-127.0.0.1:11434}"
+ALIAS_PREFIX="${ALIAS_PREFIX:-LLM-FuZe-}"
+ALIAS_SUFFIX="${ALIAS_SUFFIX:-}"
+BASE=""
+GPU=""
+NUM_GPU=""
+NUM_CTX="${BENCH_NUM_CTX:-4096}"
+QUANTIZE=""                                    # e.g. q4_K_M (passed to create; part of the name)
+FORCE=0                                        # overwrite existing outputs
+CREATE=0                                       # ollama create -f after render
+GPU_CHECK=1                                    # validate --gpu against nvidia-smi
+STORE="${OLLAMA_MODELS_DIR:-/FuZe/models/ollama}"  # store locked (shared) during --create
+OLLAMA_BIN="${OLLAMA_BIN:-$(command -v ollama || true)}"
+. "${SCRIPT_DIR}/store-lock.sh"
+. "${SCRIPT_DIR}/bake-record.sh"
+. "${SCRIPT_DIR}/../common/naming.sh"
+
+###############################################################################
+usage() {
+  cat <<USAGE
+Usage: $(basename "$0") --base MODEL --gpu GPU --num-gpu N [options]
+
+Options:
+  --base MODEL            Base model tag (e.g. gemma3:4b-it-fp16)
+  --gpu GPU               Target GPU: label (nvidia-3090ti) or name ("3090 Ti")
+  --num-gpu N             Layers to offload (PARAMETER num_gpu)
+  --num-ctx N             Context length (PARAMETER num_ctx, default: $NUM_CTX)
+  --quantize TAG          Quantization passed to create (e.g. q4_K_M); named <alias>-<TAG>-…
+  --out DIR               Output directory (default: $OUT_DIR)
+  --template FILE         Modelfile template (default: templates/Modelfile.template)
+  --host HOST:PORT        Daemon used by --create (default: $HOST)
+  --create                Create the variant from the rendered Modelfile (ollama create -f)
+  --ollama-bin PATH       ollama binary used by --create (default: auto-detect)
+  --force                 Overwrite existing Modelfile/name outputs
+  --no-gpu-check          Skip validating --gpu against the hardware inventory
+  --force-break-lock      Proceed without the store lock after the wait times out
+  -h|--help               This help
+
+Outputs (in --out):
+  <variant>.Modelfile     Rendered Modelfile
+  <variant>.name          Canonical variant name (single line)
+
+Examples:
+  $(basename "$0") --base gemma3:4b-it-fp16 --gpu nvidia-3090ti --num-gpu 28
+  $(basename "$0") --base llama3.1:8b --gpu 5090 --num-gpu 33 --num-ctx 8192 --create
+USAGE
+}
+
+###############################################################################
+# Parse CLI
+###############################################################################
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --base)         BASE="$2"; shift 2;;
+    --gpu)          GPU="$2"; shift 2;;
+    --num-gpu)      NUM_GPU="$2"; shift 2;;
+    --num-ctx)      NUM_CTX="$2"; shift 2;;
+    --quantize)     QUANTIZE="$2"; shift 2;;
+    --out)          OUT_DIR="$2"; shift 2;;
+    --template)     TEMPLATE="$2"; shift 2;;
+    --host)         HOST="$2"; shift 2;;
+    --create)       CREATE=1; shift;;
+    --ollama-bin)   OLLAMA_BIN="$2"; shift 2;;
+    --force)        FORCE=1; shift;;
+    --no-gpu-check) GPU_CHECK=0; shift;;
+    --force-break-lock) FORCE_BREAK_LOCK=1; shift;;
+    -h|--help)      usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+
+[ -n "$BASE" ]    || { echo "ERROR: --base is required" >&2; usage; exit 2; }
+[ -n "$GPU" ]     || { echo "ERROR: --gpu is required" >&2; usage; exit 2; }
+[[ "$NUM_GPU" =~ ^[0-9]+$ ]] || { echo "ERROR: --num-gpu must be an integer (got '${NUM_GPU}')" >&2; exit 2; }
+[[ "$NUM_CTX" =~ ^[0-9]+$ ]] || { echo "ERROR: --num-ctx must be an integer (got '${NUM_CTX}')" >&2; exit 2; }
+[ -f "$TEMPLATE" ] || { echo "ERROR: template not found: $TEMPLATE" >&2; exit 2; }
+
+need() { command -v "$1" >/dev/null 2>&1 || { echo "Missing dependency: $1" >&2; exit 1; }; }
+need sed
+need awk
+if [ "$CREATE" -eq 1 ]; then
+  need curl; need jq
+  [ -n "$OLLAMA_BIN" ] && [ -x "$OLLAMA_BIN" ] || { echo "ERROR: 'ollama' not found (needed by --create); set --ollama-bin PATH" >&2; exit 1; }
+fi
+
+###############################################################################
+# Helpers (naming: common/naming.sh)
+###############################################################################
+gpu_inventory_labels(){
+  nvidia-smi --query-gpu=name --format=csv,noheader 2>/dev/null \
+    | while IFS= read -r n; do [ -n "$n" ] && normalize_gpu_label "$n"; done | sort -u
+}
+
+###############################################################################
+# Main
+###############################################################################
+gpu_label="$(normalize_gpu_label "$GPU")"
+if [ "$GPU_CHECK" -eq 1 ]; then
+  if ! command -v nvidia-smi >/dev/null 2>&1; then
+    echo "ERROR: nvidia-smi not found; cannot validate --gpu (use --no-gpu-check)" >&2
+    exit 1
+  fi
+  inventory="$(gpu_inventory_labels)"
+  if ! printf '%s\n' "$inventory" | grep -Fxq "$gpu_label"; then
+    echo "ERROR: GPU '${gpu_label}' not present on this host. Available:" >&2
+    printf '  %s\n' $inventory >&2
+    exit 1
+  fi
+fi
+
+variant="$(variant_name "$BASE" "$gpu_label" "$NUM_GPU" "$QUANTIZE")"
+mf="${OUT_DIR}/${variant}.Modelfile"
+nf="${OUT_DIR}/${variant}.name"
+
+mkdir -p "$OUT_DIR"
+for f in "$mf" "$nf"; do
+  if [ -e "$f" ] && [ "$FORCE" -ne 1 ]; then
+    echo "ERROR: refusing to overwrite $f (use --force)" >&2
+    exit 1
+  fi
+done
+
+sed -E \
+  -e "s#__BASE_MODEL__#${BASE}#g" \
+  -e "s#__VARIANT__#${variant}#g" \
+  -e "s#__GPU_LABEL__#${gpu_label}#g" \
+  -e "s#__NUM_GPU__#${NUM_GPU}#g" \
+  -e "s#__NUM_CTX__#${NUM_CTX}#g" \
+  "$TEMPLATE" > "$mf"
+echo "$variant" > "$nf"
+
+echo "Variant   : $variant"
+echo "Modelfile : $mf"
+[ -n "$QUANTIZE" ] && echo "Quantize  : $QUANTIZE"
+
+[ "$CREATE" -eq 1 ] || exit 0
+
+# Shared: other bakes may run, cleanup/migrate/import may not
+store_lock "$STORE" shared
+
+echo "== Creating ${variant} on ${HOST} =="
+# From the rendered Modelfile itself, so whatever the template adds (SYSTEM,
+# TEMPLATE, more PARAMETERs) is what gets created
+status=0
+OLLAMA_HOST="http://${HOST}" "$OLLAMA_BIN" create "$variant" -f "$mf" ${QUANTIZE:+--quantize "$QUANTIZE"} 2>&1 \
+  | sed 's/^/  /' || status=$?
+
+if [ "$status" -ne 0 ]; then
+  echo "ERROR: create failed for ${variant}" >&2
+  exit 1
+fi
+echo "Created: ${variant}:latest"
+
+# Record the base digest baked from, for variants-stale.sh
+record_bake "$STORE" "$HOST" "$variant" "$BASE"
@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+# bake-record.sh — record which base digest a variant was baked from (source this file)
+# Appends {ts, variant, base, base_digest} to <store>/.fuze-bakes.jsonl under
+# flock; variants-stale.sh compares base_digest with the base's current
+# manifest digest. Used by every path that creates variants (bake-modelfile.sh,
+# benchmark.sh), so recorded staleness doesn't depend on which one baked it.
+#
+#   record_bake STORE HOST:PORT VARIANT BASE    never fails; warns when it can't record
+
+record_bake(){ # store host variant base
+  local store="${1%/}" host="$2" variant="$3" base="$4" digest
+  case "$base" in *:*) ;; *) base="${base}:latest";; esac
+  digest="$(curl -fsS --max-time 10 "http://${host}/api/tags" 2>/dev/null \
+    | jq -r --arg b "$base" '.models[]? | select(.name == $b) | .digest' 2>/dev/null | head -n1 || true)"
+  if [ -z "$digest" ] || [ ! -d "$store" ]; then
+    echo "! base digest for ${base} unknown; ${variant} will only be checked by inference" >&2
+    return 0
+  fi
+  jq -cn --argjson t "$(date +%s)" --arg v "$variant" --arg b "$base" --arg d "sha256:${digest#sha256:}" \
+    '{ts:$t, variant:$v, base:$b, base_digest:$d}' \
+    | flock -w 30 "${store}/.fuze-bakes.jsonl" tee -a "${store}/.fuze-bakes.jsonl" >/dev/null \
+    || echo "! could not record bake in ${store}/.fuze-bakes.jsonl" >&2
+  return 0
+}
@@ -0,0 +1,205 @@
+#!/usr/bin/env bash
+# bake-sweep.sh
+# num_gpu (and optionally num_ctx) sweep for one base model on one GPU:
+# bake each variant via bake-modelfile.sh --create, bench it, record tok/s and
+# VRAM per configuration, then delete the losers unless --keep-all.
+# Each variant is warmed up, then measured with one generation (bench-gen.sh,
+# as benchmark.sh scores its published tag), so load time is not in tok/s.
+# Writes a sweep CSV and the winning configuration as JSON (for promotion).
+
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+
+# ------------------------------------------------------------------------------
+# Paths & logging (LOG_DIR fallback: common/store.sh)
+# ------------------------------------------------------------------------------
+. "${SCRIPT_DIR}/../common/store.sh"
+init_log_dir
+
+# ------------------------------------------------------------------------------
+# Config (override via flags / env)
+# ------------------------------------------------------------------------------
+HOST="${HOST:-127.0.0.1:11434}"
+BASE=""
+GPU=""
+NG_LIST="${NUM_GPU_CANDIDATES:-16 24 32 40 48 56 64 72 80}"
+CTX_LIST="${BENCH_NUM_CTX:-4096}"
+KEEP_ALL=0
+GPU_CHECK_FLAG=""
+PROMPT="${PROMPT:-Tell me a 1-sentence fun fact about GPUs.}"
+BENCH_NUM_PREDICT="${BENCH_NUM_PREDICT:-64}"
+TEMPERATURE="${TEMPERATURE:-0.0}"
+TIMEOUT_GEN="${TIMEOUT_GEN:-300}"
+WARMUP_NUM_PREDICT="${WARMUP_NUM_PREDICT:-64}"
+STORE="${OLLAMA_MODELS_DIR:-/FuZe/models/ollama}"
+. "${SCRIPT_DIR}/store-lock.sh"
+. "${SCRIPT_DIR}/bench-gen.sh"
+
+readonly TS="${RUN_TS:-$(date +%Y%m%d_%H%M%S)}"
+readonly SWEEP_CSV="${LOG_DIR}/ollama_sweep_${TS}.csv"
+readonly SWEEP_BEST="${LOG_DIR}/ollama_sweep_${TS}.best.json"
+readonly MF_DIR="${LOG_DIR}/modelfiles_${TS}"
+
+usage(){
+  cat <<USAGE
+Usage: $(basename "$0") --base MODEL --gpu GPU [options]
+
+Options:
+  --base MODEL           Base model tag (e.g. gemma3:4b-it-fp16)
+  --gpu GPU              Target GPU label/name (validated like bake-modelfile.sh)
+  --num-gpu "N N ..."    num_gpu values to try (default: "$NG_LIST")
+  --num-ctx "N N ..."    num_ctx values to try (default: "$CTX_LIST")
+  --host HOST:PORT       Daemon to bake and bench on (default: $HOST)
+  --keep-all             Keep every baked variant (default: keep the winner only)
+  --no-gpu-check         Skip validating --gpu against nvidia-smi
+  --force-break-lock     Proceed without the store lock after the wait times out
+  -h|--help              This help
+
+With more than one num_ctx, variant names carry it (<alias>-ctx<N>-<gpu>-ng<N>).
+Values are tried low->high per num_ctx; the first configuration that fails
+to load (OOM / error / 0 tok/s) stops the sweep for that num_ctx and the
+higher num_gpu values are recorded as skipped. Each variant gets a warm-up
+request (WARMUP_NUM_PREDICT tokens) before the measured one.
+
+Outputs:
+  ${LOG_DIR}/ollama_sweep_<ts>.csv        one row per configuration
+  ${LOG_DIR}/ollama_sweep_<ts>.best.json  winning configuration
+USAGE
+}
+
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --base)         BASE="$2"; shift 2;;
+    --gpu)          GPU="$2"; shift 2;;
+    --num-gpu)      NG_LIST="$2"; shift 2;;
+    --num-ctx)      CTX_LIST="$2"; shift 2;;
+    --host)         HOST="$2"; shift 2;;
+    --keep-all)     KEEP_ALL=1; shift;;
+    --no-gpu-check) GPU_CHECK_FLAG="--no-gpu-check"; shift;;
+    --force-break-lock) FORCE_BREAK_LOCK=1; shift;;
+    -h|--help)      usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+
+[ -n "$BASE" ] || { echo "ERROR: --base is required" >&2; usage; exit 2; }
+[ -n "$GPU" ]  || { echo "ERROR: --gpu is required" >&2; usage; exit 2; }
+export FORCE_BREAK_LOCK   # store-lock.sh in the bake-modelfile.sh children too
+
+# ------------------------------------------------------------------------------
+# UI helpers
+# ------------------------------------------------------------------------------
+c_bold="\033[1m"; c_red="\033[31m"; c_green="\033[32m"; c_yellow="\033[33m"; c_reset="\033[0m"
+info(){ echo -e "${c_bold}==${c_reset} $*"; }
+ok(){ echo -e "${c_green}✔${c_reset} $*"; }
+warn(){ echo -e "${c_yellow}!${c_reset} $*"; }
+err(){ echo -e "${c_red}✖${c_reset} $*" >&2; }
+
+need(){ command -v "$1" >/dev/null 2>&1 || { err "Missing dependency: $1"; exit 1; }; }
+need curl; need jq; need awk; need sort
+
+# ------------------------------------------------------------------------------
+# Helpers
+# ------------------------------------------------------------------------------
+bake(){ # ng ctx -> echoes variant name; non-zero on failure
+  local ng="$1" ctx="$2" suffix="${ALIAS_SUFFIX:-}" out
+  # Sweeping several num_ctx: name each one (…-ctx<N>-<gpu>-ng<N>) so passes don't overwrite each other
+  [ "$MULTI_CTX" -eq 1 ] && suffix="${suffix}-ctx${ctx}"
+  out="$(ALIAS_SUFFIX="$suffix" "${SCRIPT_DIR}/bake-modelfile.sh" --base "$BASE" --gpu "$GPU" --num-gpu "$ng" --num-ctx "$ctx" \
+          --out "${MF_DIR}/ctx${ctx}" --host "$HOST" --create --force $GPU_CHECK_FLAG 2>&1)" || {
+    echo "$out" | sed 's/^/    /' >&2; return 1; }
+  echo "$out" | sed -nE 's/^Variant   : (.*)$/\1/p'
+}
+
+bench(){ # variant -> echoes tok/s (0.00 on failure)
+  warm_up "$HOST" "$1"
+  gen_tokps "$HOST" "$1" "$PROMPT" \
+    "$(jq -cn --argjson np "$BENCH_NUM_PREDICT" --argjson t "$TEMPERATURE" '{num_predict:$np, temperature:$t}')"
+}
+
+vram_mib(){ # variant -> MiB resident in VRAM per /api/ps (empty if unknown)
+  curl -fsS --max-time 5 "http://${HOST}/api/ps" 2>/dev/null \
+    | jq -r --arg m "$1" '.models[]? | select(.name==$m or .model==$m) | ((.size_vram // 0) / 1048576 | floor)' 2>/dev/null \
+    | head -n1 || true
+}
+
+delete_variant(){ # variant
+  store_lock_run "$STORE" exclusive curl -fsS --max-time 30 -X DELETE -H 'Content-Type: application/json' \
+    -d "$(jq -cn --arg m "$1" '{model:$m}')" "http://${HOST}/api/delete" >/dev/null 2>&1 \
+    && info "Removed variant: $1" || warn "Could not remove variant: $1"
+}
+
+# ------------------------------------------------------------------------------
+# Sweep
+# ------------------------------------------------------------------------------
+mkdir -p "$MF_DIR"
+echo "ts,base_model,variant,num_gpu,num_ctx,tokens_per_sec,vram_mib,status" > "$SWEEP_CSV"
+
+info "Sweep      : ${BASE} on ${GPU} via ${HOST}"
+info "num_gpu    : ${NG_LIST}"
+info "num_ctx    : ${CTX_LIST}"
+info "CSV        : ${SWEEP_CSV}"
+
+ngs_sorted="$(printf '%s\n' $NG_LIST | sort -n -u)"
+MULTI_CTX=0; [ "$(printf '%s\n' $CTX_LIST | sort -u | wc -l)" -gt 1 ] && MULTI_CTX=1
+baked=()
+
+for ctx in $CTX_LIST; do
+  stop=0
+  for ng in $ngs_sorted; do
+    if [ "$stop" -eq 1 ]; then
+      echo "${TS},${BASE},,${ng},${ctx},0.00,,skipped" >> "$SWEEP_CSV"
+      continue
+    fi
+    info "num_gpu=${ng} num_ctx=${ctx}"
+    if ! variant="$(bake "$ng" "$ctx")" || [ -z "$variant" ]; then
+      warn "  bake failed; skipping higher num_gpu for num_ctx=${ctx}"
+      echo "${TS},${BASE},,${ng},${ctx},0.00,,create_failed" >> "$SWEEP_CSV"
+      stop=1; continue
+    fi
+    baked+=("$variant")
+    tokps="$(bench "${variant}:latest")"
+    vram="$(vram_mib "${variant}:latest")"
+    if awk -v t="$tokps" 'BEGIN{exit !(t+0==0)}'; then
+      warn "  ${variant} failed to load/generate; skipping higher num_gpu for num_ctx=${ctx}"
+      echo "${TS},${BASE},${variant},${ng},${ctx},${tokps},${vram},load_failed" >> "$SWEEP_CSV"
+      stop=1; continue
+    fi
+    ok "  ${variant}: ${tokps} tok/s${vram:+, ${vram} MiB VRAM}"
+    echo "${TS},${BASE},${variant},${ng},${ctx},${tokps},${vram},ok" >> "$SWEEP_CSV"
+  done
+done
+
+# ------------------------------------------------------------------------------
+# Rank, record winner, GC
+# ------------------------------------------------------------------------------
+echo
+info "Ranked results (tok/s desc)"
+ranked="$(awk -F',' 'NR>1 && $8=="ok"' "$SWEEP_CSV" | sort -t',' -k6,6nr -k4,4n)"
+if [ -z "$ranked" ]; then
+  err "No configuration worked for ${BASE} on ${GPU}"
+  winner=""
+else
+  printf '%s\n' "$ranked" | awk -F',' '{printf "  %2d. %-60s ng=%-4s ctx=%-6s %8s tok/s  %s MiB\n", NR, $3, $4, $5, $6, ($7==""?"?":$7)}'
+  winner="$(printf '%s\n' "$ranked" | head -n1)"
+  IFS=',' read -r _ w_base w_variant w_ng w_ctx w_tokps w_vram _ <<<"$winner"
+  jq -n \
+    --arg base "$w_base" --arg gpu "$GPU" --arg variant "$w_variant" \
+    --argjson ng "$w_ng" --argjson ctx "$w_ctx" --argjson tokps "$w_tokps" \
+    --arg vram "$w_vram" --arg csv "$SWEEP_CSV" --arg host "$HOST" --arg ts "$TS" \
+    '{ts:$ts, host:$host, base_model:$base, gpu:$gpu, variant:$variant, num_gpu:$ng, num_ctx:$ctx,
+      tokens_per_sec:$tokps, vram_mib:(if $vram=="" then null else ($vram|tonumber) end), results_csv:$csv}' \
+    > "$SWEEP_BEST"
+  ok "Winner     : ${w_variant} (ng=${w_ng}, ctx=${w_ctx}) at ${w_tokps} tok/s"
+  info "Best JSON  : ${SWEEP_BEST}"
+fi
+
+if [ "$KEEP_ALL" -eq 0 ]; then
+  for v in $(printf '%s\n' ${baked[@]+"${baked[@]}"} | sort -u); do
+    [ -n "$winner" ] && [ "$v" = "${w_variant:-}" ] && continue
+    delete_variant "$v"
+  done
+fi
+
+[ -n "$winner" ] || exit 1
@@ -0,0 +1,34 @@
+#!/usr/bin/env bash
+# bench-gen.sh — warm-up and tok/s measurement shared by benchmark.sh and
+# bake-sweep.sh (source this file), so both score a configuration the same way.
+# Reads TIMEOUT_GEN (default 300) and WARMUP_NUM_PREDICT (default 64).
+#
+#   calc_tokps EVAL_COUNT EVAL_DURATION_NS   -> tok/s
+#   warm_up HOST:PORT MODEL                  load the model; result ignored
+#   gen_tokps HOST:PORT MODEL PROMPT OPTS    one generation -> tok/s (0.00 on error)
+
+# Ollama durations are in nanoseconds; convert to seconds for tok/s
+calc_tokps(){ awk -v ec="$1" -v ed="$2" 'BEGIN{ if(ed<=0){print "0.00"} else {printf "%.2f", (ec+0.0)/(ed/1000000000.0)} }'; }
+
+# Loading the weights dominates a cold first request; keep it out of the measurement
+warm_up(){ # ep model
+  local payload
+  payload="$(jq -cn --arg m "$2" --argjson np "${WARMUP_NUM_PREDICT:-64}" \
+    '{model:$m, prompt:"warm up", stream:false, options:{num_predict:$np}}')"
+  curl -sS --max-time "${TIMEOUT_GEN:-300}" -H 'Content-Type: application/json' -d "$payload" \
+    "http://$1/api/generate" >/dev/null 2>&1 || true
+  sleep 1
+}
+
+gen_tokps(){ # ep model prompt options_json
+  local payload o ec ed
+  payload="$(jq -cn --arg m "$2" --arg p "$3" --argjson o "$4" '{model:$m, prompt:$p, stream:false, options:$o}')"
+  o="$(curl -sS --max-time "${TIMEOUT_GEN:-300}" -H 'Content-Type: application/json' -d "$payload" \
+        "http://$1/api/generate" 2>/dev/null || true)"
+  if [ -z "$o" ] || jq -e '.error' >/dev/null 2>&1 <<<"$o"; then
+    echo "0.00"; return 0
+  fi
+  ec="$(jq -r '.eval_count // 0' <<<"$o" 2>/dev/null || echo 0)"
+  ed="$(jq -r '.eval_duration // 0' <<<"$o" 2>/dev/null || echo 0)"
+  calc_tokps "$ec" "$ed"
+}
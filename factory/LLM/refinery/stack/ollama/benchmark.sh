@@ -15,12 +15,9 @@ set -euo pipefail
 # Paths & logging
 # ------------------------------------------------------------------------------
 ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
-LOG_DIR="${LOG_DIR:-/var/log/fuze-stack}"
 # Ensure writable log dir; fall back to per-user location if repo logs are root-owned
-if ! mkdir -p "$LOG_DIR" 2>/dev/null || [ ! -w "$LOG_DIR" ]; then
-  LOG_DIR="${XDG_STATE_HOME:-$HOME/.local/state}/fuze-stack/logs"
-  mkdir -p "$LOG_DIR" 2>/dev/null || { LOG_DIR="$HOME/.fuze/stack/logs"; mkdir -p "$LOG_DIR"; }
-fi
+. "${ROOT_DIR}/common/store.sh"
+init_log_dir
 
 # ------------------------------------------------------------------------------
 # Config (override via env)
@@ -84,6 +81,15 @@ WARMUP_NUM_PREDICT="${WARMUP_NUM_PREDICT:-64}"
 # Binary
 readonly OLLAMA_BIN="${OLLAMA_BIN:-/usr/local/bin/ollama}"
 
+# Store lock: bakes hold it shared, variant removals exclusive (see store-lock.sh)
+. "$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)/store-lock.sh"
+# Bakes record their base digest for variants-stale.sh (record_bake)
+. "$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)/bake-record.sh"
+# Variant naming (normalize_gpu_label, base_alias)
+. "$(cd "$(dirname "${BASH_SOURCE[0]}")/../common" && pwd)/naming.sh"
+# calc_tokps, warm_up (shared with bake-sweep.sh)
+. "$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)/bench-gen.sh"
+
 # Derived
 readonly HOSTNAME_NOW="$(hostname -s 2>/dev/null || hostname)"
 readonly TS="${RUN_TS:-$(date +%Y%m%d_%H%M%S)}"
@@ -118,9 +124,6 @@ need curl; need jq; need awk; need sed; need systemctl
 # ------------------------------------------------------------------------------
 # HTTP helpers
 # ------------------------------------------------------------------------------
-# Ollama durations are in nanoseconds; convert to seconds for tok/s
-calc_tokps(){ awk -v ec="$1" -v ed="$2" 'BEGIN{ if(ed<=0){print "0.00"} else {printf "%.2f", (ec+0.0)/(ed/1000000000.0)} }'; }
-
 curl_tags(){ local ep="$1"; curl -fsS --max-time "$TIMEOUT_TAGS" "http://${ep}/api/tags" || return 1; }
 
 curl_gen(){
@@ -215,18 +218,6 @@ offload_triplet(){ # name,uuid,memmib for an ollama-test-* unit
   fi
 }
 
-normalize_gpu_label(){
-  # "NVIDIA GeForce RTX 5090" -> nvidia-5090 ; "NVIDIA GeForce RTX 3090 Ti" -> nvidia-3090ti
-  local raw="$1" s
-  s="$(echo "$raw" | tr '[:upper:]' '[:lower:]')"
-  s="${s//nvidia /}"
-  s="${s//geforce /}"
-  s="${s//rtx /}"
-  s="${s// /}"
-  s="${s//ti/ti}"
-  echo "nvidia-${s//super/super}"
-}
-
 gpu_label_for_ep(){
   local ep="$1" unit lbl name uuid mem
   unit="$(unit_for_ep "$ep")"
@@ -298,17 +289,18 @@ bake_variant(){ # newname base num_gpu
     echo "PARAMETER num_gpu ${ng}"
   } >"$tf"
   OLLAMA_HOST="http://${PULL_FROM}" \
-    "$OLLAMA_BIN" create "$newname" -f "$tf" >>"$CREATE_LOG" 2>&1 || {
+    store_lock_run "$OLLAMA_MODELS_DIR" shared "$OLLAMA_BIN" create "$newname" -f "$tf" >>"$CREATE_LOG" 2>&1 || {
       rm -f "$tf"; return 1; }
   rm -f "$tf"
   echo "$newname" >> "$CREATED_LIST"
+  record_bake "$OLLAMA_MODELS_DIR" "$PULL_FROM" "$newname" "$base"
   return 0
 }
 
 rm_variant_tag(){ # name[:tag]
   local full="$1"
   info "Removing variant tag: $full"
-  OLLAMA_HOST="http://${PULL_FROM}" "$OLLAMA_BIN" rm "$full" 2>/dev/null || true
+  OLLAMA_HOST="http://${PULL_FROM}" store_lock_run "$OLLAMA_MODELS_DIR" exclusive "$OLLAMA_BIN" rm "$full" 2>/dev/null || true
 }
 
 wait_variant_visible(){ # ep variant secs
@@ -324,26 +316,16 @@ wait_variant_visible(){ # ep variant secs
   return 1
 }
 
-base_alias(){ # "llama4:16x17b" -> "llama4-16x17b" with compact suffixes
-  local s
-  s="$(echo "$1" | sed -E 's#[/:]+#-#g')"
-  # Compact common tokens: it->i, fp16->f16, bf16->b16
-  s="${s//-it-/-i-}"
-  s="${s%-it}"; s="${s%-i}"; # no-op cleanup if ends with -it, we transform below
-  s="${s//-it/-i}"
-  s="${s//-fp16/-f16}"
-  s="${s//-bf16/-b16}"
-  echo "$s"
-}
-
 discover_models(){
   info "Discovering base models from persistent daemon (:${PERSISTENT_PORT})"
   local names out=()
   names="$(OLLAMA_HOST="http://${PULL_FROM}" "$OLLAMA_BIN" list 2>/dev/null | awk '($1!="NAME" && $1!=""){print $1}')"
   while IFS= read -r tag; do
     [ -z "$tag" ] && continue
-    # Skip our optimized variants: anything with -nvidia-...-ngNN in the NAME portion
-    if echo "$tag" | grep -Eq -- '-nvidia-[a-z0-9]+(super|ti)?-ng[0-9]+(:|$)'; then
+    # Skip our optimized variants (-nvidia-<gpu>-ngNN in the NAME portion), the
+    # serving aliases promote-variant.sh copies them to (-nvidia-<gpu>) and its
+    # backups of replaced aliases (-nvidia-<gpu>-prev-<ts>)
+    if echo "$tag" | grep -Eq -- '-nvidia-[a-z0-9]+(super|ti)?(-ng[0-9]+|-prev-[0-9_]+)?(:|$)'; then
       continue
     fi
     # Optional include/exclude filters
@@ -552,12 +534,7 @@ tune_and_bench_one(){ # ep baseTag aliasBase
       wait_variant_visible "$ep" "${pub_name}:latest" 12 || true
       ok " Published: ${pub_name}:latest"
       # Optional warm-up request to reduce cold-start skew
-      if [ "$WARMUP_PUBLISH" -eq 1 ]; then
-        local wu_req
-        wu_req="$(jq -cn --arg m "${pub_name}:latest" --arg p "warm up" --argjson np "$WARMUP_NUM_PREDICT" '{model:$m, prompt:$p, stream:false, num_predict:$np}')"
-        curl -sS -H 'Content-Type: application/json' -d "$wu_req" "http://${ep}/api/generate" >/dev/null 2>&1 || true
-        sleep 1
-      fi
+      [ "$WARMUP_PUBLISH" -eq 1 ] && warm_up "$ep" "${pub_name}:latest"
       # Re-bench the published tag so CSV contains an explicit row for it
       local pub_tokps
       pub_tokps="$(bench_once "$ep" "$base" "${pub_name}:latest" "published" "$best_ng" "$gpu_lbl" || echo 0.00)"
@@ -12,9 +12,14 @@ HOSTS="127.0.0.1:11434"                       # space-separated list
 MATCH_RE='.*-nvidia-.*-ng[0-9]+(:[[:alnum:]._-]+)?$'  # what to delete
 KEEP_RE=''                                     # exclude anything matching this
 CREATED_LIST=''                                # optional file: only delete names listed here
+UNUSED_DAYS=''                                 # optional: only delete names unused this long
+STALE=0                                        # 1: only delete variants whose base changed
 FORCE=0                                        # 0=dry-run, 1=delete
 YES=0                                          # suppress prompt if FORCE=1
 OLLAMA_BIN="${OLLAMA_BIN:-$(command -v ollama || true)}"
+STORE="${OLLAMA_MODELS_DIR:-/FuZe/models/ollama}"  # local store to lock while deleting
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+. "${SCRIPT_DIR}/store-lock.sh"
 
 ###############################################################################
 usage() {
@@ -27,9 +32,15 @@ Options:
   --keep  REGEX               Regex of names to keep (exclude)
   --from-created FILE         Only remove names present in this file
                               (lines like: my-variant-name OR my-variant-name:latest)
+  --unused-days N             Only remove names not used in N days (store-usage.sh
+                              data; manifest mtime when no usage is recorded;
+                              kept with a warning when neither is available)
+  --stale                     Only remove variants reported stale by variants-stale.sh
   --force                     Actually delete (otherwise dry-run)
   --yes                       Don't prompt when --force is set
   --ollama-bin PATH           Path to ollama binary (default: auto-detect)
+  --store PATH                Local model store to lock (default: $STORE)
+  --force-break-lock          Proceed without the store lock after the wait times out
   -h|--help                   This help
 
 Examples:
@@ -54,9 +65,13 @@ while [ $# -gt 0 ]; do
     --match)        MATCH_RE="$2"; shift 2;;
     --keep)         KEEP_RE="$2"; shift 2;;
     --from-created) CREATED_LIST="$2"; shift 2;;
+    --unused-days)  UNUSED_DAYS="$2"; shift 2;;
+    --stale)        STALE=1; shift;;
     --force)        FORCE=1; shift;;
     --yes)          YES=1; shift;;
     --ollama-bin)   OLLAMA_BIN="$2"; shift 2;;
+    --store)        STORE="$2"; shift 2;;
+    --force-break-lock) FORCE_BREAK_LOCK=1; shift;;
     -h|--help)      usage; exit 0;;
     *) echo "Unknown arg: $1" >&2; usage; exit 2;;
   esac
@@ -97,6 +112,41 @@ filter_by_created_list() {
   rm -f /tmp/created.$$.lst
 }
 
+# Keep only names whose last use is older than UNUSED_DAYS (unknown last use: kept)
+filter_by_usage() {
+  if [ -z "${UNUSED_DAYS:-}" ]; then cat; return 0; fi
+  local cutoff name full rel t
+  cutoff=$(( $(date +%s) - UNUSED_DAYS * 86400 ))
+  while IFS= read -r name; do
+    [ -n "$name" ] || continue
+    case "$name" in *:*) full="$name";; *) full="$name:latest";; esac
+    t="$(awk -F'\t' -v n="$full" '$1==n{print $2; exit}' <<<"$USAGE_TSV")"
+    if [ -z "$t" ]; then
+      rel="${full%:*}"; case "$rel" in */*) ;; *) rel="library/$rel";; esac
+      t="$(stat -c '%Y' "$STORE/manifests/registry.ollama.ai/${rel}/${full##*:}" 2>/dev/null || true)"
+    fi
+    if [ -z "$t" ]; then
+      # No usage record and no local manifest (e.g. a remote host): never delete on a guess
+      echo "  ! keeping ${name}: last use unknown (no usage record or manifest in ${STORE})" >&2
+      continue
+    fi
+    [ "$t" -lt "$cutoff" ] && echo "$name"
+  done
+  return 0
+}
+
+# Keep only names listed in STALE_LIST (variants-stale.sh --names, with tags)
+filter_by_stale() {
+  if [ "$STALE" -ne 1 ]; then cat; return 0; fi
+  local name full
+  while IFS= read -r name; do
+    [ -n "$name" ] || continue
+    case "$name" in *:*) full="$name";; *) full="$name:latest";; esac
+    grep -Fxq "$full" <<<"$STALE_LIST" && echo "$name"
+  done
+  return 0
+}
+
 apply_match_keep() { # stdin list -> stdout filtered
   if [ -n "$MATCH_RE" ]; then
     grep -E "$MATCH_RE" || true
@@ -132,6 +182,19 @@ delete_one() { # host name
 ###############################################################################
 # Main
 ###############################################################################
+USAGE_TSV=""
+if [ -n "$UNUSED_DAYS" ]; then
+  [[ "$UNUSED_DAYS" =~ ^[0-9]+$ ]] || { echo "ERROR: --unused-days must be an integer" >&2; exit 2; }
+  USAGE_TSV="$("${SCRIPT_DIR}/store-usage.sh" last-used --store "$STORE")" \
+    || echo "WARN: no usage data for $STORE; falling back to manifest mtime (unreliable: ollama touches files)" >&2
+fi
+
+STALE_LIST=""
+if [ "$STALE" -eq 1 ]; then
+  STALE_LIST="$("${SCRIPT_DIR}/variants-stale.sh" --store "$STORE" --names)" \
+    || { echo "ERROR: variants-stale.sh failed for $STORE" >&2; exit 1; }
+fi
+
 total_removed=0
 total_candidates=0
 
@@ -150,6 +213,8 @@ for host in $HOSTS; do
   candidates="$(printf "%s\n" "$names" \
       | filter_by_created_list \
       | apply_match_keep \
+      | filter_by_usage \
+      | filter_by_stale \
       | sort -u)"
   if [ -z "$candidates" ]; then
     echo "  Nothing matched."
@@ -167,6 +232,10 @@ for host in $HOSTS; do
     continue
   fi
 
+  # No deleting while a bake/import holds the store (no-op if STORE is absent;
+  # re-entrant, so later hosts reuse the lock taken for the first)
+  store_lock "$STORE" exclusive
+
   removed=0
   # Delete both with and without :latest where applicable
   while IFS= read -r name; do
@@ -0,0 +1,245 @@
+#!/usr/bin/env bash
+# promote-variant.sh
+# Point a stable serving alias at a baked variant, with a journal for rollback.
+#   promote <best.json>            winner from bake-sweep.sh (variant + tok/s)
+#   promote --variant NAME         explicit variant (optionally --score TOKPS)
+#   rollback                       restore the alias's previous target
+# The alias defaults to the variant name without its -ng<NUM> suffix
+# (<alias>-<gpu>), i.e. one serving tag per base model and GPU. The replaced
+# target is kept as <alias>-prev-<ts>; benchmark.sh discovery skips both.
+
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+
+# ------------------------------------------------------------------------------
+# Paths & logging (LOG_DIR fallback, with_latest: common/store.sh)
+# ------------------------------------------------------------------------------
+. "${SCRIPT_DIR}/../common/store.sh"
+init_log_dir
+
+# ------------------------------------------------------------------------------
+# Config (override via flags / env)
+# ------------------------------------------------------------------------------
+HOST="${HOST:-127.0.0.1:11434}"
+JOURNAL="${JOURNAL:-${LOG_DIR}/ollama_promotions.jsonl}"
+NOTIFY_WEBHOOK="${NOTIFY_WEBHOOK:-}"     # optional: POST a JSON event per action
+MAX_REGRESS_PCT="${MAX_REGRESS_PCT:-5}"  # refuse if score drops more than this
+TIMEOUT_GEN="${TIMEOUT_GEN:-120}"
+ACTION=""
+RESULTS=""
+VARIANT=""
+ALIAS=""
+SCORE=""
+FORCE=0
+STORE="${OLLAMA_MODELS_DIR:-/FuZe/models/ollama}"
+. "$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)/store-lock.sh"
+
+readonly TS="$(date +%Y%m%d_%H%M%S)"
+
+usage(){
+  cat <<USAGE
+Usage:
+  $(basename "$0") promote <best.json> [options]
+  $(basename "$0") promote --variant NAME [--score TOKPS] [options]
+  $(basename "$0") rollback --alias NAME [options]
+  $(basename "$0") status [--alias NAME]
+
+Options:
+  --variant NAME        Variant to promote (instead of a results JSON)
+  --score TOKPS         Benchmark score for --variant (tokens/sec)
+  --alias NAME          Serving alias (default: variant without -ng<NUM>)
+  --host HOST:PORT      Daemon to act on (default: $HOST)
+  --journal FILE        Promotion journal (default: $JOURNAL)
+  --max-regress PCT     Refuse when score drops more than PCT% vs current (default: $MAX_REGRESS_PCT)
+  --notify URL          POST each promotion/rollback event to this webhook
+  --force               Promote despite a score regression
+  -h|--help             This help
+USAGE
+}
+
+ACTION="${1:-}"
+case "$ACTION" in
+  promote|rollback|status) shift;;
+  -h|--help) usage; exit 0;;
+  *) echo "Unknown action: ${ACTION:-<none>}" >&2; usage; exit 2;;
+esac
+
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --variant)     VARIANT="$2"; shift 2;;
+    --score)       SCORE="$2"; shift 2;;
+    --alias)       ALIAS="$2"; shift 2;;
+    --host)        HOST="$2"; shift 2;;
+    --journal)     JOURNAL="$2"; shift 2;;
+    --max-regress) MAX_REGRESS_PCT="$2"; shift 2;;
+    --notify)      NOTIFY_WEBHOOK="$2"; shift 2;;
+    --force)       FORCE=1; shift;;
+    -h|--help)     usage; exit 0;;
+    -*) echo "Unknown arg: $1" >&2; usage; exit 2;;
+    *)  RESULTS="$1"; shift;;
+  esac
+done
+
+# ------------------------------------------------------------------------------
+# UI helpers
+# ------------------------------------------------------------------------------
+c_bold="\033[1m"; c_red="\033[31m"; c_green="\033[32m"; c_yellow="\033[33m"; c_reset="\033[0m"
+info(){ echo -e "${c_bold}==${c_reset} $*"; }
+ok(){ echo -e "${c_green}✔${c_reset} $*"; }
+warn(){ echo -e "${c_yellow}!${c_reset} $*"; }
+err(){ echo -e "${c_red}✖${c_reset} $*" >&2; }
+
+need(){ command -v "$1" >/dev/null 2>&1 || { err "Missing dependency: $1"; exit 1; }; }
+need curl; need jq; need awk
+
+# ------------------------------------------------------------------------------
+# Helpers
+# ------------------------------------------------------------------------------
+
+model_exists(){ # name -> 0 if listed by /api/tags
+  local n; n="$(with_latest "$1")"
+  curl -fsS --max-time 10 "http://${HOST}/api/tags" 2>/dev/null \
+    | jq -r '.models[]?.name' | grep -Fxq "$n"
+}
+
+health_gen(){ # name -> 0 if a tiny generation returns text
+  local payload o
+  payload="$(jq -cn --arg m "$(with_latest "$1")" '{model:$m, prompt:"ping", stream:false, options:{num_predict:8}}')"
+  o="$(curl -sS --max-time "$TIMEOUT_GEN" -H 'Content-Type: application/json' -d "$payload" "http://${HOST}/api/generate" || true)"
+  [ -n "$(jq -r '.response // empty' <<<"$o" 2>/dev/null || true)" ]
+}
+
+copy_model(){ # source destination
+  store_lock_run "$STORE" shared curl -fsS --max-time 60 -H 'Content-Type: application/json' \
+    -d "$(jq -cn --arg s "$(with_latest "$1")" --arg d "$(with_latest "$2")" '{source:$s, destination:$d}')" \
+    "http://${HOST}/api/copy" >/dev/null
+}
+
+# Replay the journal for an alias: promote pushes, rollback pops.
+# Echoes the stack as JSON array of {variant, score} (top = current target).
+alias_stack(){ # alias
+  [ -s "$JOURNAL" ] || { echo '[]'; return 0; }
+  jq -sc --arg a "$1" --arg h "$HOST" '
+    reduce (.[] | select(.alias==$a and .host==$h)) as $e ([];
+      if $e.action=="promote" then . + [{variant:$e.variant, score:$e.score}]
+      elif $e.action=="rollback" then .[:-1]
+      else . end)' "$JOURNAL"
+}
+
+record(){ # action alias variant score previous previous_score
+  local ev
+  ev="$(jq -cn --arg ts "$TS" --arg h "$HOST" --arg act "$1" --arg a "$2" --arg v "$3" \
+        --arg s "$4" --arg p "$5" --arg ps "$6" \
+        '{ts:$ts, host:$h, action:$act, alias:$a, variant:$v,
+          score:(if $s=="" then null else ($s|tonumber) end),
+          previous:(if $p=="" then null else $p end),
+          previous_score:(if $ps=="" then null else ($ps|tonumber) end)}')"
+  mkdir -p "$(dirname "$JOURNAL")"
+  echo "$ev" >> "$JOURNAL"
+  if [ -n "$NOTIFY_WEBHOOK" ]; then
+    curl -fsS --max-time 10 -H 'Content-Type: application/json' -d "$ev" "$NOTIFY_WEBHOOK" >/dev/null 2>&1 \
+      || warn "Webhook notification failed: $NOTIFY_WEBHOOK"
+  fi
+}
+
+# ------------------------------------------------------------------------------
+# Actions
+# ------------------------------------------------------------------------------
+do_status(){
+  [ -s "$JOURNAL" ] || { info "No promotions recorded in $JOURNAL"; return 0; }
+  local aliases
+  if [ -n "$ALIAS" ]; then aliases="$ALIAS"; else
+    aliases="$(jq -r --arg h "$HOST" 'select(.host==$h) | .alias' "$JOURNAL" | sort -u)"; fi
+  for a in $aliases; do
+    alias_stack "$a" | jq -r --arg a "$a" '
+      if length==0 then "\($a): (nothing promoted)"
+      else "\($a) -> \(.[-1].variant) (score=\(.[-1].score // "?"))" +
+           (if length>1 then "  [previous: \(.[-2].variant)]" else "" end) end'
+  done
+}
+
+do_promote(){
+  if [ -n "$RESULTS" ]; then
+    [ -f "$RESULTS" ] || { err "Results file not found: $RESULTS"; exit 2; }
+    VARIANT="${VARIANT:-$(jq -r '.variant // empty' "$RESULTS")}"
+    SCORE="${SCORE:-$(jq -r '.tokens_per_sec // empty' "$RESULTS")}"
+  fi
+  [ -n "$VARIANT" ] || { err "No variant given (results JSON or --variant)"; exit 2; }
+  # Before any API call: a non-numeric score would slip past the regression check
+  # and then fail the journal write after the alias had already moved
+  if [ -n "$SCORE" ] && ! [[ "$SCORE" =~ ^[0-9]+(\.[0-9]+)?$ ]]; then
+    err "Invalid score '${SCORE}' (tokens/sec, e.g. 42.5)"; exit 2
+  fi
+  [[ "$MAX_REGRESS_PCT" =~ ^[0-9]+(\.[0-9]+)?$ ]] || { err "Invalid --max-regress '${MAX_REGRESS_PCT}'"; exit 2; }
+  ALIAS="${ALIAS:-$(echo "$VARIANT" | sed -E 's/:[^:]*$//; s/-ng[0-9]+$//')}"
+  [ "$ALIAS" != "$VARIANT" ] || { err "Alias equals variant name; pass --alias"; exit 2; }
+
+  info "Promote    : ${VARIANT} -> ${ALIAS} on ${HOST}"
+  model_exists "$VARIANT" || { err "Variant not found on ${HOST}: ${VARIANT}"; exit 1; }
+  health_gen "$VARIANT"   || { err "Health generation failed for ${VARIANT}"; exit 1; }
+  ok "Health check passed"
+
+  local stack cur cur_score
+  stack="$(alias_stack "$ALIAS")"
+  cur="$(jq -r '.[-1].variant // empty' <<<"$stack")"
+  cur_score="$(jq -r '.[-1].score // empty' <<<"$stack")"
+
+  if [ "$cur" = "$VARIANT" ]; then
+    ok "${ALIAS} already points at ${VARIANT}"; return 0
+  fi
+
+  # A scored target is never replaced blind: no candidate score means no regression guard
+  if [ -n "$cur_score" ] && [ -z "$SCORE" ]; then
+    if [ "$FORCE" -eq 1 ]; then
+      warn "No score for ${VARIANT}; current ${cur} scores ${cur_score} tok/s; continuing (--force)"
+    else
+      err "Refusing: no score for ${VARIANT} but current ${cur} scores ${cur_score} tok/s. Pass --score or a results JSON, or use --force."
+      exit 1
+    fi
+  fi
+  if [ -n "$cur_score" ] && [ -n "$SCORE" ]; then
+    if awk -v n="$SCORE" -v c="$cur_score" -v p="$MAX_REGRESS_PCT" 'BEGIN{exit !(n < c*(1-p/100.0))}'; then
+      if [ "$FORCE" -eq 1 ]; then
+        warn "Score regression ${cur_score} -> ${SCORE} tok/s (>${MAX_REGRESS_PCT}%); continuing (--force)"
+      else
+        err "Refusing: ${VARIANT} scores ${SCORE} tok/s vs current ${cur} at ${cur_score} (>${MAX_REGRESS_PCT}% drop). Use --force."
+        exit 1
+      fi
+    fi
+  fi
+
+  # Alias exists but was never promoted by us: keep its target restorable
+  if [ -z "$cur" ] && model_exists "$ALIAS"; then
+    cur="${ALIAS}-prev-${TS}"
+    copy_model "$ALIAS" "$cur" || { err "Could not back up existing ${ALIAS}"; exit 1; }
+    record promote "$ALIAS" "$cur" "" "" ""
+    info "Backed up existing ${ALIAS} as ${cur}"
+  fi
+
+  copy_model "$VARIANT" "$ALIAS" || { err "Copy ${VARIANT} -> ${ALIAS} failed"; exit 1; }
+  record promote "$ALIAS" "$VARIANT" "$SCORE" "$cur" "$cur_score"
+  ok "Promoted   : ${ALIAS} -> ${VARIANT}${SCORE:+ (${SCORE} tok/s)}${cur:+ (previous: ${cur})}"
+}
+
+do_rollback(){
+  [ -n "$ALIAS" ] || { err "rollback requires --alias"; exit 2; }
+  local stack cur prev prev_score
+  stack="$(alias_stack "$ALIAS")"
+  cur="$(jq -r '.[-1].variant // empty' <<<"$stack")"
+  prev="$(jq -r 'if length>1 then .[-2].variant else empty end' <<<"$stack")"
+  prev_score="$(jq -r 'if length>1 then (.[-2].score // empty) else empty end' <<<"$stack")"
+  [ -n "$prev" ] || { err "No previous target recorded for ${ALIAS}"; exit 1; }
+  model_exists "$prev" || { err "Previous target no longer exists on ${HOST}: ${prev}"; exit 1; }
+
+  info "Rollback   : ${ALIAS} ${cur} -> ${prev}"
+  copy_model "$prev" "$ALIAS" || { err "Copy ${prev} -> ${ALIAS} failed"; exit 1; }
+  record rollback "$ALIAS" "$prev" "$prev_score" "$cur" ""
+  ok "Rolled back: ${ALIAS} -> ${prev}"
+}
+
+case "$ACTION" in
+  promote)  do_promote ;;
+  rollback) do_rollback ;;
+  status)   do_status ;;
+esac
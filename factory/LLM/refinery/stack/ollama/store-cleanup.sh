@@ -3,19 +3,35 @@
 # Same-FS: rename/merge with dedupe, progress, USR1 status.
 # Diff-FS: rsync missing files with --remove-source-files, then prune.
 # Idempotent & safe to re-run.
+# --verify full|fast: nothing at the source is deleted until store-verify.sh
+# has checked the destination copies; any mismatch halts before deletion.
 
 set -euo pipefail
 
 CANON="${CANON:-/FuZe/models/ollama}"
 ALT_DEFAULT="/FuZe/ollama/models"
 STOP_SERVICES=1
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+VERIFY="${VERIFY:-none}"                 # none | fast | full
+VERIFY_JOBS="${VERIFY_JOBS:-}"
+VERIFY_BUFSZ="${VERIFY_BUFSZ:-}"
+VERIFY_REPORT="${VERIFY_REPORT:-}"
+. "${SCRIPT_DIR}/store-lock.sh"
 
 usage() {
   cat <<USAGE
-Usage: $(basename "$0") [--canon PATH] [--alt PATH] [--no-stop]
+Usage: $(basename "$0") [--canon PATH] [--alt PATH] [--no-stop] [--verify full|fast|none]
   --canon PATH    Canonical store (default: $CANON)
   --alt PATH      Alternate store to migrate (default: $ALT_DEFAULT)
   --no-stop       Do NOT stop ollama services before merging
+  --verify MODE   Verify copies before deleting anything at the source:
+                  full = sha256 vs digest name + source, manifests byte-for-byte
+                  fast = blob size, manifest bytes (mtime is deliberately not compared:
+                         a same-FS dedupe keeps its own);  none = previous behavior (default: $VERIFY)
+  --verify-jobs N       Parallel hashing workers (default: nproc)
+  --verify-bufsz SIZE   Hash read buffer, dd syntax (default: 4M)
+  --verify-report FILE  Verification report (default: <canon>/../store-verify-<ts>.tsv)
+  --force-break-lock    Proceed without the store locks after the wait times out
 USAGE
 }
 
@@ -25,14 +41,22 @@ while [ $# -gt 0 ]; do
     --canon) CANON="$2"; shift 2;;
     --alt) ALT="$2"; shift 2;;
     --no-stop) STOP_SERVICES=0; shift 1;;
+    --verify) VERIFY="$2"; shift 2;;
+    --verify-jobs) VERIFY_JOBS="$2"; shift 2;;
+    --verify-bufsz) VERIFY_BUFSZ="$2"; shift 2;;
+    --verify-report) VERIFY_REPORT="$2"; shift 2;;
+    --force-break-lock) FORCE_BREAK_LOCK=1; shift 1;;
     -h|--help) usage; exit 0;;
     *) echo "Unknown arg: $1" >&2; usage; exit 2;;
   esac
 done
 
+case "$VERIFY" in none|fast|full) ;; *) echo "Invalid --verify: $VERIFY (full|fast|none)" >&2; exit 2;; esac
+
 echo "== ollama-store-cleanup =="
 echo "Canonical store : $CANON"
 echo "Alt candidate   : $ALT"
+echo "Verify          : $VERIFY"
 
 mkdir -p "$CANON"
 if [ ! -d "$ALT" ]; then
@@ -44,6 +68,10 @@ if [ "$ALT" = "$CANON" ]; then
   exit 0
 fi
 
+# Exclusive on both stores: no bake/import/cleanup may run during the merge
+store_lock "$CANON" exclusive
+store_lock "$ALT" exclusive
+
 # Optionally stop services to avoid churn
 if [ "$STOP_SERVICES" -eq 1 ]; then
   echo "-- stopping Ollama services (will not fail if missing)"
@@ -83,6 +111,39 @@ force_progress() {
 }
 trap force_progress USR1
 
+# ---- Verification (deferred source deletion) ---------------------------------
+DEFER_LIST="$(mktemp)"
+trap 'store_lock_cleanup; rm -f "$DEFER_LIST"' EXIT
+
+# With --verify, duplicates are only listed here and deleted after verification
+remove_dupe() { # src_file rel
+  if [ "$VERIFY" = "none" ]; then
+    rm -f -- "$1" || true
+  else
+    echo "$2" >> "$DEFER_LIST"
+  fi
+}
+
+verify_or_halt() { # list_file
+  local args=(--src "$ALT" --dst "$CANON" --mode "$VERIFY" --list "$1"
+              --report "${VERIFY_REPORT:-$(dirname "$CANON")/store-verify-$(date +%Y%m%d_%H%M%S).tsv}")
+  [ -n "$VERIFY_JOBS" ] && args+=(--jobs "$VERIFY_JOBS")
+  [ -n "$VERIFY_BUFSZ" ] && args+=(--bufsz "$VERIFY_BUFSZ")
+  if ! "$SCRIPT_DIR/store-verify.sh" "${args[@]}"; then
+    echo "!! Verification failed — nothing deleted from $ALT. Inspect the report above." >&2
+    exit 3
+  fi
+}
+
+delete_verified() { # list_file
+  local n=0
+  while IFS= read -r rel; do
+    [ -n "$rel" ] || continue
+    rm -f -- "$ALT/$rel" && n=$((n+1))
+  done < "$1"
+  echo "   deleted $n verified source files"
+}
+
 # ---- Same-FS merge with dedupe & progress -----------------------------------
 same_fs_merge_dir() {
   local SRC="$1" DST="$2" label="$3"
@@ -101,11 +162,11 @@ same_fs_merge_dir() {
     if [ -e "$t" ]; then
       if [ "$label" = "blobs" ]; then
         # blobs are content-addressed; identical filename => identical content
-        rm -f -- "$f" || true
+        remove_dupe "$f" "$label/$rel"
         REMOVED=$((REMOVED+1))
       else
         if cmp -s -- "$f" "$t"; then
-          rm -f -- "$f" || true
+          remove_dupe "$f" "$label/$rel"
           REMOVED=$((REMOVED+1))
         else
           echo "\n!! differs, keeping ALT copy: $label/$rel" >&2
@@ -135,9 +196,22 @@ diff_fs_rsync_dir() {
   [ -d "$SRC" ] || { echo "   [$label] nothing to sync (missing)"; return 0; }
   mkdir -p "$DST"
   echo "   rsync $label -> $DST"
-  rsync -aHAX --ignore-existing --remove-source-files --info=stats1,progress2 \
-    "$SRC/" "$DST/" || true
-  find "$SRC" -depth -type d -empty -delete || true
+  if [ "$VERIFY" = "none" ]; then
+    rsync -aHAX --ignore-existing --remove-source-files --info=stats1,progress2 \
+      "$SRC/" "$DST/" || true
+    find "$SRC" -depth -type d -empty -delete || true
+  else
+    # keep sources until verified; queue every source file for checking
+    rsync -aHAX --ignore-existing --info=stats1,progress2 "$SRC/" "$DST/" || true
+    (cd "$(dirname "$SRC")" && find "$label" -type f | sort) >> "$DEFER_LIST"
+  fi
+}
+
+# ALT is empty apart from our own lock files: drop the lock and remove it
+retire_alt_if_empty() {
+  [ -z "$(find "$ALT" -mindepth 1 -maxdepth 1 ! -name '.fuze-store.lock*' -print -quit 2>/dev/null)" ] || return 1
+  store_lock_release "$ALT" remove
+  rmdir -p "$ALT" 2>/dev/null || true
 }
 
 # ---- Strategy selection ------------------------------------------------------
@@ -147,9 +221,9 @@ if [ "$dev_can" = "$dev_alt" ]; then
   same_fs_merge_dir "$ALT/manifests" "$CANON/manifests" "manifests"
 
   # If ALT subtree is empty now, remove it
-  if [ -z "$(find "$ALT" -mindepth 1 -print -quit 2>/dev/null)" ]; then
-    rmdir -p "$ALT" 2>/dev/null || true
-  else
+  if retire_alt_if_empty; then
+    :
+  elif [ ! -s "$DEFER_LIST" ]; then
     echo "   NOTE: $ALT not empty (likely conflicts or unexpected files kept)."
     echo "         Inspect with: find \"$ALT\" -type f | head"
   fi
@@ -159,6 +233,15 @@ else
   diff_fs_rsync_dir "$ALT/manifests" "$CANON/manifests" "manifests"
 fi
 
+# Verify, then delete deferred source files
+if [ "$VERIFY" != "none" ] && [ -s "$DEFER_LIST" ]; then
+  echo "-- verifying $(wc -l < "$DEFER_LIST" | tr -d ' ') files before deleting sources ($VERIFY)"
+  verify_or_halt "$DEFER_LIST"
+  delete_verified "$DEFER_LIST"
+  find "$ALT/blobs" "$ALT/manifests" -depth -type d -empty -delete 2>/dev/null || true
+  retire_alt_if_empty || true
+fi
+
 # Final perms (best effort)
 chmod 755 /FuZe /FuZe/models "$CANON" 2>/dev/null || true
 
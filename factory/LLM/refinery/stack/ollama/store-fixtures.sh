@@ -0,0 +1,231 @@
+#!/usr/bin/env bash
+# store-fixtures.sh — build a synthetic Ollama store for exercising store tooling
+# (store-cleanup, store-verify, store-quota, variants-stale, cleanup-variants)
+# without touching /FuZe. Everything is generated from a declarative JSON spec:
+# bases with weight blobs, variants baked from them with the canonical
+# <prefix><alias>-<gpu>-ng<N> names, and deliberate corruptions on request.
+# Blob contents are derived from their labels, so the same spec always yields
+# the same digests. A fixture.json next to the store records what was built;
+# --check re-reads the tree and confirms it matches.
+
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+. "${SCRIPT_DIR}/../common/naming.sh"
+. "${SCRIPT_DIR}/../common/store.sh"
+
+OUT=""
+SPEC=""
+CHECK=0
+FORCE=0
+
+read -r -d '' DEFAULT_SPEC <<'JSON' || true
+{
+  "prefix": "LLM-FuZe-",
+  "bases": [
+    {"name": "gemma3:4b-it-fp16", "weights_kib": 512},
+    {"name": "llama3.1:8b",       "weights_kib": 1024}
+  ],
+  "variants": [
+    {"base": "gemma3:4b-it-fp16", "gpu": "nvidia-3090ti", "num_gpu": [16, 24, 32]},
+    {"base": "llama3.1:8b",       "gpu": "nvidia-5090",   "num_gpu": [33], "quantize_kib": 256}
+  ],
+  "corrupt": {
+    "missing_blobs": 0,
+    "orphan_blobs": 0,
+    "malformed_names": 0,
+    "double_prefix": 0,
+    "hardlinked_dupes": 0
+  }
+}
+JSON
+
+usage(){
+  cat <<USAGE
+Usage: $(basename "$0") --out DIR [--spec FILE.json] [--force]
+       $(basename "$0") --check --out DIR
+
+Options:
+  --out DIR       Store to create (DIR/blobs, DIR/manifests, DIR/fixture.json)
+  --spec FILE     JSON spec (default: built-in; print it with --print-spec)
+  --force         Replace an existing DIR
+  --check         Verify DIR against its fixture.json
+  --print-spec    Print the default spec and exit
+
+Spec corruptions (counts):
+  missing_blobs     delete blobs referenced by variants
+  orphan_blobs      blobs no manifest references
+  malformed_names   variant manifests without the -ng<N> suffix
+  double_prefix     variant names with the prefix twice
+  hardlinked_dupes  hardlink that many blobs into DIR.alt (same-FS store-cleanup)
+USAGE
+}
+
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --out)        OUT="$2"; shift 2;;
+    --spec)       SPEC="$2"; shift 2;;
+    --force)      FORCE=1; shift;;
+    --check)      CHECK=1; shift;;
+    --print-spec) echo "$DEFAULT_SPEC"; exit 0;;
+    -h|--help)    usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+[ -n "$OUT" ] || { echo "ERROR: --out is required" >&2; usage; exit 2; }
+
+need(){ command -v "$1" >/dev/null 2>&1 || { echo "Missing dependency: $1" >&2; exit 1; }; }
+need jq; need sha256sum
+
+# ------------------------------------------------------------------------------
+# Helpers (variant naming: common/naming.sh; manifest paths: common/store.sh)
+# ------------------------------------------------------------------------------
+blob(){ # label kib -> "sha256:<hex> <size>"; content derived from label
+  local tmp d
+  tmp="$OUT/.blob.tmp"
+  yes "$1" | head -c "$(( $2 * 1024 ))" > "$tmp" || true
+  d="$(sha256sum "$tmp" | awk '{print $1}')"
+  mv -f "$tmp" "$OUT/blobs/sha256-$d"
+  echo "sha256:$d $(( $2 * 1024 ))"
+}
+
+write_manifest(){ # name weights_digest weights_size label
+  local name="$1" rel cfg params
+  rel="$(manifest_rel "$name")"
+  read -r cfg _ <<<"$(blob "config:$4" 1)"
+  read -r params _ <<<"$(blob "params:$4" 1)"
+  mkdir -p "$OUT/$(dirname "$rel")"
+  jq -n --arg c "$cfg" --arg w "$2" --argjson ws "$3" --arg p "$params" \
+    '{schemaVersion:2, mediaType:"application/vnd.docker.distribution.manifest.v2+json",
+      config:{mediaType:"application/vnd.docker.container.image.v1+json", digest:$c, size:1024},
+      layers:[{mediaType:"application/vnd.ollama.image.model", digest:$w, size:$ws},
+              {mediaType:"application/vnd.ollama.image.params", digest:$p, size:1024}]}' > "$OUT/$rel"
+  echo "$rel"
+}
+
+# ------------------------------------------------------------------------------
+# --check: every recorded manifest parses back to its name and its blobs (and
+# the orphan blobs) match their digests; recorded hardlinks are still links
+# ------------------------------------------------------------------------------
+if [ "$CHECK" -eq 1 ]; then
+  fx="$OUT/fixture.json"
+  [ -f "$fx" ] || { echo "ERROR: no fixture.json in $OUT" >&2; exit 2; }
+  bad=0
+  while IFS=$'\t' read -r name rel missing; do
+    [ "$(manifest_rel "$name")" = "$rel" ] && [ "$(manifest_name "$rel")" = "$(with_latest "$name")" ] \
+      || { echo "✖ name/path round-trip: $name <-> $rel"; bad=$((bad+1)); }
+    [ -f "$OUT/$rel" ] || { echo "✖ manifest missing: $rel"; bad=$((bad+1)); continue; }
+    while IFS= read -r d; do
+      f="$OUT/blobs/${d/sha256:/sha256-}"
+      [ "$d" = "$missing" ] && continue
+      [ -f "$f" ] && [ "sha256:$(sha256sum "$f" | awk '{print $1}')" = "$d" ] \
+        || { echo "✖ blob missing/corrupt: $d ($name)"; bad=$((bad+1)); }
+    done < <(jq -r '.config.digest, .layers[].digest' "$OUT/$rel")
+  done < <(jq -r '.models[] | [.name, .manifest, (.missing_blob // "-")] | @tsv' "$fx")
+  while IFS= read -r d; do
+    [ -f "$OUT/blobs/${d/sha256:/sha256-}" ] && { echo "✖ expected-missing blob present: $d"; bad=$((bad+1)); }
+  done < <(jq -r '.models[].missing_blob // empty' "$fx")
+  while IFS= read -r d; do
+    f="$OUT/blobs/${d/sha256:/sha256-}"
+    [ -f "$f" ] && [ "sha256:$(sha256sum "$f" | awk '{print $1}')" = "$d" ] \
+      || { echo "✖ orphan blob missing/corrupt: $d"; bad=$((bad+1)); }
+  done < <(jq -r '.orphan_blobs[]?' "$fx")
+  while IFS=$'\t' read -r d alt; do
+    [ "$alt/blobs/${d/sha256:/sha256-}" -ef "$OUT/blobs/${d/sha256:/sha256-}" ] \
+      || { echo "✖ hardlink missing: $alt/blobs/${d/sha256:/sha256-}"; bad=$((bad+1)); }
+  done < <(jq -r '.hardlinks[]? | [.hardlink, .alt] | @tsv' "$fx")
+  want="$(jq '[.models[].manifest] | length' "$fx")"
+  have="$(find "$OUT/manifests" -type f | wc -l | tr -d ' ')"
+  [ "$want" = "$have" ] || { echo "✖ manifest count: fixture $want, tree $have"; bad=$((bad+1)); }
+  [ "$bad" -eq 0 ] && { echo "✔ $OUT matches fixture.json ($want manifests)"; exit 0; }
+  echo "$bad problem(s)"; exit 1
+fi
+
+# ------------------------------------------------------------------------------
+# Generate
+# ------------------------------------------------------------------------------
+if [ -n "$SPEC" ]; then spec="$(jq -c '.' "$SPEC")"; else spec="$(jq -c '.' <<<"$DEFAULT_SPEC")"; fi
+if [ -e "$OUT" ]; then
+  [ "$FORCE" -eq 1 ] || { echo "ERROR: $OUT exists (use --force)" >&2; exit 1; }
+  [ -f "$OUT/fixture.json" ] || { echo "ERROR: refusing to replace $OUT: not a generated fixture" >&2; exit 1; }
+  # DIR.alt only goes with DIR if this fixture made it (recorded, holding just its blobs)
+  if [ -e "$OUT.alt" ]; then
+    jq -e --arg a "$OUT.alt" '.hardlinks | any(.alt == $a)' "$OUT/fixture.json" >/dev/null 2>&1 \
+      && [ -z "$(find "$OUT.alt" -mindepth 1 -maxdepth 1 ! -name blobs ! -name '.fuze-store.lock*' -print -quit)" ] \
+      || { echo "ERROR: refusing to replace $OUT.alt: not generated with $OUT" >&2; exit 1; }
+    rm -rf "$OUT.alt"
+  fi
+  rm -rf "$OUT"
+fi
+if [ "$(jq '.corrupt.hardlinked_dupes // 0' <<<"$spec")" -gt 0 ] && [ -e "$OUT.alt" ]; then
+  echo "ERROR: $OUT.alt exists and is not a generated fixture; move it away first" >&2; exit 1
+fi
+mkdir -p "$OUT/blobs" "$OUT/manifests"
+prefix="$(jq -r '.prefix // "LLM-FuZe-"' <<<"$spec")"
+records="$OUT/.records.jsonl"; : > "$records"
+
+declare -A W_DIGEST W_SIZE
+while IFS=$'\t' read -r name kib; do
+  read -r d s <<<"$(blob "weights:$name" "$kib")"
+  W_DIGEST[$name]="$d"; W_SIZE[$name]="$s"
+  rel="$(write_manifest "$name" "$d" "$s" "$name")"
+  jq -cn --arg n "$name" --arg r "$rel" '{name:$n, manifest:$r, kind:"base"}' >> "$records"
+done < <(jq -r '.bases[] | [.name, (.weights_kib // 512)] | @tsv' <<<"$spec")
+
+variant_names=()
+while IFS=$'\t' read -r base gpu ng qkib; do
+  [ -n "${W_DIGEST[$base]:-}" ] || { echo "ERROR: variant base not in spec bases: $base" >&2; exit 2; }
+  name="$(ALIAS_PREFIX="$prefix" ALIAS_SUFFIX="" variant_name "$base" "$gpu" "$ng")"
+  if [ "$qkib" -gt 0 ]; then read -r d s <<<"$(blob "weights:$name" "$qkib")"
+  else d="${W_DIGEST[$base]}"; s="${W_SIZE[$base]}"; fi
+  rel="$(write_manifest "$name" "$d" "$s" "$name")"
+  jq -cn --arg n "$name" --arg r "$rel" --arg b "$base" --arg g "$gpu" --argjson ng "$ng" --argjson q "$([ "$qkib" -gt 0 ] && echo true || echo false)" \
+    '{name:$n, manifest:$r, kind:"variant", base:$b, gpu:$g, num_gpu:$ng, quantized:$q}' >> "$records"
+  variant_names+=("$name")
+done < <(jq -r '.variants[] | .num_gpu[] as $ng | [.base, .gpu, $ng, (.quantize_kib // 0)] | @tsv' <<<"$spec")
+
+c(){ jq -r --arg k "$1" '.corrupt[$k] // 0' <<<"$spec"; }
+first_base="$(jq -r '.bases[0].name' <<<"$spec")"
+
+for i in $(seq 1 "$(c malformed_names)"); do
+  name="${prefix}$(base_alias "$first_base")-nvidia-malformed${i}-ng"
+  rel="$(write_manifest "$name" "${W_DIGEST[$first_base]}" "${W_SIZE[$first_base]}" "$name")"
+  jq -cn --arg n "$name" --arg r "$rel" '{name:$n, manifest:$r, kind:"malformed_name"}' >> "$records"
+done
+for i in $(seq 1 "$(c double_prefix)"); do
+  name="${prefix}${prefix}$(base_alias "$first_base")-nvidia-dup${i}-ng${i}"
+  rel="$(write_manifest "$name" "${W_DIGEST[$first_base]}" "${W_SIZE[$first_base]}" "$name")"
+  jq -cn --arg n "$name" --arg r "$rel" '{name:$n, manifest:$r, kind:"double_prefix"}' >> "$records"
+done
+for i in $(seq 1 "$(c orphan_blobs)"); do
+  read -r d _ <<<"$(blob "orphan:$i" 4)"
+  jq -cn --arg d "$d" '{orphan_blob:$d}' >> "$records"
+done
+# Missing: drop the params blob of the first N variants (weights stay shared)
+n_missing="$(c missing_blobs)"
+for ((i=0; i<n_missing && i<${#variant_names[@]}; i++)); do
+  name="${variant_names[$i]}"; rel="$(manifest_rel "$name")"
+  d="$(jq -r '.layers[] | select(.mediaType | endswith("params")) | .digest' "$OUT/$rel")"
+  rm -f "$OUT/blobs/${d/sha256:/sha256-}"
+  jq -cn --arg n "$name" --arg d "$d" '{missing_for:$n, missing_blob:$d}' >> "$records"
+done
+n_links="$(c hardlinked_dupes)"
+if [ "$n_links" -gt 0 ]; then
+  mkdir -p "$OUT.alt/blobs"
+  find "$OUT/blobs" -type f -name 'sha256-*' | sort | head -n "$n_links" | while IFS= read -r f; do
+    ln "$f" "$OUT.alt/blobs/$(basename "$f")"
+    jq -cn --arg d "$(basename "$f" | sed 's/^sha256-/sha256:/')" --arg a "$OUT.alt" '{hardlink:$d, alt:$a}' >> "$records"
+  done
+fi
+
+jq -s --argjson spec "$spec" '
+  (map(select(.missing_for)) | map({key:.missing_for, value:.missing_blob}) | from_entries) as $miss
+  | {spec:$spec,
+     models: [ .[] | select(.manifest) | . + (if $miss[.name] then {missing_blob:$miss[.name]} else {} end) ],
+     orphan_blobs: [ .[] | .orphan_blob // empty ],
+     hardlinks: [ .[] | select(.hardlink) ]}' "$records" > "$OUT/fixture.json"
+rm -f "$records"
+
+echo "✔ Fixture store: $OUT"
+jq -r '"  manifests: \(.models | length) (\([.models[] | select(.kind=="variant")] | length) variants), orphan blobs: \(.orphan_blobs | length), missing blobs: \([.models[] | select(.missing_blob)] | length), hardlinks: \(.hardlinks | length)"' "$OUT/fixture.json"
+echo "  blobs: $(find "$OUT/blobs" -type f | wc -l | tr -d ' ') files, $(du -sh "$OUT/blobs" | awk '{print $1}')"
@@ -0,0 +1,148 @@
+#!/usr/bin/env bash
+# store-lock.sh — advisory lock for an Ollama model store (source this file)
+# Destructive operations (cleanup, migrate, import, variant rm) take the lock
+# exclusive; creates take it shared so concurrent bakes don't block each other
+# but a cleanup can never delete blobs out from under a running create.
+# The lock is flock(1) on <store>/.fuze-store.lock (released automatically when
+# the holder exits). Every holder, shared or exclusive, records itself in
+# <store>/.fuze-store.lock.d/<host>.<pid> for contention messages and stale
+# detection; the record is removed on exit (dead local pids are pruned).
+#
+#   store_lock STORE exclusive|shared        hold until the calling script exits
+#                                            (re-entrant per store)
+#   store_lock_run STORE exclusive|shared CMD...   hold only for CMD
+#   store_lock_release STORE [remove]        drop it early; "remove" also deletes
+#                                            the lock files and log (store being retired)
+#
+# Env: STORE_LOCK_TIMEOUT (seconds to wait, default 600)
+#      FORCE_BREAK_LOCK=1 (proceed without the lock after the timeout; logged)
+
+STORE_LOCK_TIMEOUT="${STORE_LOCK_TIMEOUT:-600}"
+FORCE_BREAK_LOCK="${FORCE_BREAK_LOCK:-0}"
+declare -A STORE_LOCK_FD=() STORE_LOCK_MODE=()
+
+store_lock_path(){ echo "${1%/}/.fuze-store.lock"; }
+store_lock_host(){ hostname -s 2>/dev/null || hostname; }
+store_lock_record(){ echo "$(store_lock_path "$1").d/$(store_lock_host).$$"; }
+
+store_lock_record_write(){ # store mode
+  local dir rec
+  dir="$(store_lock_path "$1").d"; rec="$(store_lock_record "$1")"
+  mkdir -p "$dir" 2>/dev/null || return 0
+  printf 'mode=%s pid=%s host=%s cmd=%s since=%s\n' "$2" "$$" "$(store_lock_host)" \
+    "$(basename "$0")" "$(date -Is)" > "${rec}.tmp" 2>/dev/null && mv -f "${rec}.tmp" "$rec"
+}
+
+# Live holders, one per line ("unknown" if none); records of dead local pids are pruned
+store_lock_holder(){ # store
+  local f h pid host found=0 me
+  me="$(store_lock_host)"
+  for f in "$(store_lock_path "$1").d"/*; do
+    [ -f "$f" ] || continue
+    h="$(cat "$f" 2>/dev/null || true)"
+    pid="$(sed -nE 's/.*pid=([0-9]+).*/\1/p' <<<"$h")"
+    host="$(sed -nE 's/.*host=([^ ]+).*/\1/p' <<<"$h")"
+    if [ "$host" = "$me" ] && [ -n "$pid" ] && ! kill -0 "$pid" 2>/dev/null; then
+      rm -f "$f" 2>/dev/null || true; continue
+    fi
+    echo "$h"; found=1
+  done
+  [ "$found" -eq 1 ] || echo "unknown"
+}
+
+# Lock is held but no recorded holder is alive: it is only kept by a descriptor
+# inherited by a child of a dead holder (or by a tool that doesn't record itself).
+store_lock_stale(){ # store -> 0 if stale
+  [ "$(store_lock_holder "$1")" = "unknown" ]
+}
+
+store_lock_broken(){ # store mode -> loud log, never fails
+  local msg
+  msg="FORCE-BREAK-LOCK: $(basename "$0") (pid $$) proceeding WITHOUT the $2 store lock on $1; holder: $(store_lock_holder "$1" | paste -sd';' -)"
+  echo -e "\033[31m!!! ${msg}\033[0m" >&2
+  command -v logger >/dev/null 2>&1 && logger -t fuze-store-lock -- "$msg" || true
+  echo "$(date -Is) $msg" >> "$(store_lock_path "$1").log" 2>/dev/null || true
+}
+
+store_lock_fail(){ # store mode
+  echo "ERROR: could not take the $2 store lock on $1 within ${STORE_LOCK_TIMEOUT}s" >&2
+  if store_lock_stale "$1"; then
+    echo "       no recorded holder is alive (stale: lock kept by an inherited descriptor)" >&2
+  else
+    store_lock_holder "$1" | sed 's/^/       holder: /' >&2
+  fi
+  echo "       re-run with --force-break-lock to proceed anyway" >&2
+}
+
+store_lock_cleanup(){ # remove this process's holder records
+  local s
+  for s in "${!STORE_LOCK_FD[@]}"; do rm -f "$(store_lock_record "$s")" 2>/dev/null || true; done
+}
+
+store_lock_on_exit(){ # prepend store_lock_cleanup to the current EXIT trap (once)
+  local prev
+  prev="$(trap -p EXIT | sed -E "s/^trap -- '(.*)' EXIT$/\1/")"
+  case "$prev" in *store_lock_cleanup*) return 0;; esac
+  # shellcheck disable=SC2064
+  trap "store_lock_cleanup${prev:+; $prev}" EXIT
+}
+
+store_lock(){ # store mode
+  local store="${1%/}" mode="$2" lock flag fd
+  [ -d "$store" ] || return 0
+  # Re-entrant: already held in this mode (or exclusive) by this process
+  if [ -n "${STORE_LOCK_FD[$store]:-}" ]; then
+    [ "${STORE_LOCK_MODE[$store]}" = "exclusive" ] || [ "$mode" = "shared" ] && return 0
+    fd="${STORE_LOCK_FD[$store]}"   # shared -> exclusive upgrade on the same fd
+  else
+    lock="$(store_lock_path "$store")"
+    if ! { exec {fd}>>"$lock"; } 2>/dev/null; then
+      echo "! cannot open store lock $lock; continuing unlocked" >&2
+      return 0
+    fi
+  fi
+  flag=-x; [ "$mode" = "shared" ] && flag=-s
+  if ! flock -n "$flag" "$fd"; then
+    echo "… waiting up to ${STORE_LOCK_TIMEOUT}s for the ${mode} store lock on ${store} (holder: $(store_lock_holder "$store" | paste -sd';' -))" >&2
+    if ! flock -w "$STORE_LOCK_TIMEOUT" "$flag" "$fd"; then
+      if [ "$FORCE_BREAK_LOCK" -eq 1 ]; then store_lock_broken "$store" "$mode"; return 0; fi
+      store_lock_fail "$store" "$mode"
+      exit 4
+    fi
+  fi
+  STORE_LOCK_FD[$store]="$fd"; STORE_LOCK_MODE[$store]="$mode"
+  store_lock_record_write "$store" "$mode"
+  store_lock_on_exit
+}
+
+store_lock_release(){ # store [remove]
+  local store="${1%/}" fd="${STORE_LOCK_FD[${1%/}]:-}"
+  rm -f "$(store_lock_record "$store")" 2>/dev/null || true
+  if [ -n "$fd" ]; then
+    exec {fd}>&-
+    unset "STORE_LOCK_FD[$store]" "STORE_LOCK_MODE[$store]"
+  fi
+  if [ "${2:-}" = "remove" ]; then
+    rm -f "$(store_lock_path "$store")" 2>/dev/null || true
+    rm -rf "$(store_lock_path "$store").d" 2>/dev/null || true
+    rm -f "$(store_lock_path "$store").log" 2>/dev/null || true
+  fi
+}
+
+store_lock_run(){ # store mode cmd...
+  local store="${1%/}" mode="$2" flag rc=0 own=0; shift 2
+  [ -d "$store" ] && [ -w "$store" ] || { "$@"; return; }
+  # Already held by this process (store_lock): the child inherits nothing to wait on
+  if [ -n "${STORE_LOCK_FD[$store]:-}" ] && { [ "${STORE_LOCK_MODE[$store]}" = "exclusive" ] || [ "$mode" = "shared" ]; }; then
+    "$@"; return
+  fi
+  flag=-x; [ "$mode" = "shared" ] && flag=-s
+  [ -f "$(store_lock_record "$store")" ] || { store_lock_record_write "$store" "$mode"; own=1; }
+  # -E 75: tell a lock timeout apart from the command's own exit status
+  flock "$flag" -E 75 -w "$STORE_LOCK_TIMEOUT" "$(store_lock_path "$store")" "$@" || rc=$?
+  [ "$own" -eq 1 ] && rm -f "$(store_lock_record "$store")" 2>/dev/null
+  [ "$rc" -eq 75 ] || return "$rc"
+  if [ "$FORCE_BREAK_LOCK" -eq 1 ]; then store_lock_broken "$store" "$mode"; "$@"; return; fi
+  store_lock_fail "$store" "$mode"
+  return 4
+}
@@ -0,0 +1,247 @@
+#!/usr/bin/env bash
+# store-quota.sh — keep an Ollama model store under a byte budget
+# Computes the unique store size (every blob counted once), and when it is over
+# LIMIT - HEADROOM proposes evicting the least-recently-used variants until the
+# blobs they alone reference bring it back under. Pinned names (pin file) and
+# the current target of every promoted alias are never proposed.
+# Dry-run by default; --execute deletes the proposal through the daemon.
+#
+# Selection is deterministic: candidates are ordered by (last_used, name) and
+# taken greedily; a variant's reclaim is the size of the blobs no remaining
+# manifest references once it (and those before it) are gone.
+
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+
+# ------------------------------------------------------------------------------
+# Paths & logging (LOG_DIR fallback, manifest names: common/store.sh)
+# ------------------------------------------------------------------------------
+. "${SCRIPT_DIR}/../common/store.sh"
+init_log_dir
+
+# ------------------------------------------------------------------------------
+# Config (override via flags / env)
+# ------------------------------------------------------------------------------
+STORE="${OLLAMA_MODELS_DIR:-/FuZe/models/ollama}"
+HOST="${HOST:-127.0.0.1:11434}"
+LIMIT="${STORE_QUOTA:-}"
+HEADROOM="${STORE_HEADROOM:-0}"
+PIN_FILE="${PIN_FILE:-}"                       # default: <store>/.fuze-pins
+JOURNAL="${JOURNAL:-${LOG_DIR}/ollama_promotions.jsonl}"
+MATCH_RE='.*-nvidia-.*-ng[0-9]+(:[[:alnum:]._-]+)?$'  # same default as cleanup-variants.sh
+EXECUTE=0
+ALLOW_PARTIAL=0
+. "${SCRIPT_DIR}/store-lock.sh"
+
+usage(){
+  cat <<USAGE
+Usage: $(basename "$0") --limit SIZE [options]
+
+Options:
+  --limit SIZE          Store budget, e.g. 800G, 1.5T, 500GiB or bytes (env STORE_QUOTA)
+  --headroom SIZE       Free this much extra below the limit (default: $HEADROOM)
+  --store PATH          Ollama model store (default: $STORE)
+  --pin-file FILE       Names never evicted, one per line, # comments (default: <store>/.fuze-pins)
+  --journal FILE        Promotion journal; current alias targets are pinned (default: $JOURNAL)
+  --match REGEX         Eviction candidates (default: baked variants, $MATCH_RE)
+  --host HOST:PORT      Daemon used by --execute (default: $HOST)
+  --execute             Delete the proposed variants (otherwise dry-run)
+  --allow-partial       With --execute, delete even when the proposal cannot get
+                        under budget (refused by default)
+  --force-break-lock    Proceed without the store lock after the wait times out
+  -h|--help             This help
+
+Exit status: 0 within budget (or the proposal gets there), 1 still over budget.
+USAGE
+}
+
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --limit)     LIMIT="$2"; shift 2;;
+    --headroom)  HEADROOM="$2"; shift 2;;
+    --store)     STORE="$2"; shift 2;;
+    --pin-file)  PIN_FILE="$2"; shift 2;;
+    --journal)   JOURNAL="$2"; shift 2;;
+    --match)     MATCH_RE="$2"; shift 2;;
+    --host)      HOST="$2"; shift 2;;
+    --allow-partial) ALLOW_PARTIAL=1; shift;;
+    --execute)   EXECUTE=1; shift;;
+    --force-break-lock) FORCE_BREAK_LOCK=1; shift;;
+    -h|--help)   usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+
+need(){ command -v "$1" >/dev/null 2>&1 || { echo "Missing dependency: $1" >&2; exit 1; }; }
+need jq; need awk; need find
+[ "$EXECUTE" -eq 1 ] && need curl
+
+# ------------------------------------------------------------------------------
+# Helpers
+# ------------------------------------------------------------------------------
+# "800G" / "1.5T" / "500GiB" / "123456" -> bytes (1024-based units)
+parse_size(){ # str
+  awk -v s="$1" 'BEGIN{
+    if (match(s, /^[0-9]+(\.[0-9]+)?/) == 0) exit 1
+    n = substr(s, 1, RLENGTH); u = toupper(substr(s, RLENGTH + 1)); sub(/I?B$/, "", u)
+    m = (u=="") ? 1 : (u=="K") ? 1024 : (u=="M") ? 1024^2 : (u=="G") ? 1024^3 : (u=="T") ? 1024^4 : -1
+    if (m < 0) exit 1
+    printf "%.0f\n", n * m }'
+}
+
+human(){ # bytes -> "12.3 GiB"
+  awk -v b="$1" 'BEGIN{ split("B KiB MiB GiB TiB", u, " "); i=1
+    while (b >= 1024 && i < 5) { b /= 1024; i++ }
+    printf (i==1 ? "%d %s" : "%.1f %s"), b, u[i] }'
+}
+
+# Recency used for LRU ordering (epoch seconds): store-usage.sh data when
+# recorded; otherwise (and for never-recorded names) the manifest mtime
+USAGE_TSV=""
+load_usage(){
+  if USAGE_TSV="$("${SCRIPT_DIR}/store-usage.sh" last-used --store "$STORE")"; then return 0; fi
+  USAGE_TSV=""
+  echo "! No usage data for $STORE; ordering by manifest mtime (ollama touches these — record usage with store-usage.sh)" >&2
+}
+
+last_used_of(){ # name manifest_path
+  local t
+  t="$(awk -F'\t' -v n="$1" '$1==n{print $2; exit}' <<<"$USAGE_TSV")"
+  echo "${t:-$(stat -c '%Y' "$2")}"
+}
+
+inventory(){ # -> JSON lines {name, last_used, blobs:[...]}
+  local m rel
+  while IFS= read -r m; do
+    rel="${m#"$STORE"/}"
+    jq -c --arg n "$(manifest_name "$rel")" --argjson t "$(last_used_of "$(manifest_name "$rel")" "$m")" \
+      '{name:$n, last_used:$t,
+        blobs:([.config.digest?] + [.layers[]?.digest] | map(select(. != null) | sub("^sha256:"; "sha256-")) | unique)}' \
+      "$m" 2>/dev/null || echo "! skipping unreadable manifest: $rel" >&2
+  done < <(find "$STORE/manifests" -type f 2>/dev/null | sort)
+}
+
+pinned_names(){ # -> names (with tag), one per line
+  if [ -f "$PIN_FILE" ]; then
+    sed -E 's/#.*$//; s/^[[:space:]]+//; s/[[:space:]]+$//' "$PIN_FILE" | awk 'NF' \
+      | while IFS= read -r n; do with_latest "$n"; done
+  fi
+  # Current target of every promoted alias (promote pushes, rollback pops)
+  if [ -s "$JOURNAL" ]; then
+    jq -sr 'group_by([.host, .alias])[]
+      | reduce .[] as $e ([]; if $e.action=="promote" then . + [$e.variant]
+                               elif $e.action=="rollback" then .[:-1] else . end)
+      | last // empty' "$JOURNAL" 2>/dev/null \
+      | while IFS= read -r n; do with_latest "$n"; done
+  fi
+}
+
+# Pure selection (stdin: {inventory, sizes, total, target, pinned, match})
+# -> {evict:[{name,last_used,reclaim}], reclaim, after, satisfied}
+read -r -d '' SELECT_JQ <<'JQ' || true
+  . as $in
+  | (reduce $in.inventory[].blobs[] as $b ({}; .[$b] += 1)) as $refs
+  | [ $in.inventory[]
+      | select(.name | test($in.match))
+      | select(.name as $n | $in.pinned | index($n) | not) ]
+  | sort_by([.last_used, .name])
+  | reduce .[] as $c ({refs:$refs, evict:[], reclaim:0};
+      if ($in.total - .reclaim) <= $in.target then .
+      else
+        (reduce $c.blobs[] as $b ({refs:.refs, freed:0};
+           .refs[$b] -= 1
+           | if .refs[$b] == 0 then .freed += ($in.sizes[$b] // 0) else . end)) as $r
+        | .refs = $r.refs
+        | .reclaim += $r.freed
+        | .evict += [{name:$c.name, last_used:$c.last_used, reclaim:$r.freed}]
+      end)
+  | {evict, reclaim, after:($in.total - .reclaim), satisfied:(($in.total - .reclaim) <= $in.target)}
+JQ
+
+# ------------------------------------------------------------------------------
+# Main
+# ------------------------------------------------------------------------------
+[ -n "$LIMIT" ] || { echo "ERROR: --limit is required" >&2; usage; exit 2; }
+limit_b="$(parse_size "$LIMIT")"    || { echo "ERROR: invalid --limit: $LIMIT" >&2; exit 2; }
+headroom_b="$(parse_size "$HEADROOM")" || { echo "ERROR: invalid --headroom: $HEADROOM" >&2; exit 2; }
+[ -d "$STORE/blobs" ] || { echo "ERROR: not an ollama store (no blobs/): $STORE" >&2; exit 2; }
+PIN_FILE="${PIN_FILE:-$STORE/.fuze-pins}"
+target_b=$(( limit_b > headroom_b ? limit_b - headroom_b : 0 ))
+
+# Executing: lock before measuring, so the plan can't go stale under a bake/import
+[ "$EXECUTE" -eq 1 ] && store_lock "$STORE" exclusive
+
+sizes_json="$(find "$STORE/blobs" -maxdepth 1 -type f -name 'sha256-*' -printf '%f\t%s\n' \
+  | jq -Rn '[inputs | split("\t") | {(.[0]): (.[1] | tonumber)}] | add // {}')"
+total_b="$(jq 'add // 0' <<<"$sizes_json")"
+
+echo "== ollama store quota =="
+echo "Store    : $STORE"
+echo "Size     : $(human "$total_b") (unique blobs)"
+echo "Limit    : $(human "$limit_b")$([ "$headroom_b" -gt 0 ] && echo " (target $(human "$target_b") with headroom)")"
+
+if [ "$total_b" -le "$target_b" ]; then
+  echo "✔ Within budget ($(human $((target_b - total_b))) to spare)."
+  exit 0
+fi
+echo "Over     : $(human $((total_b - target_b)))"
+
+load_usage
+plan="$(jq -n \
+  --argjson inv "$(inventory | jq -s '.')" \
+  --argjson sizes "$sizes_json" \
+  --argjson total "$total_b" \
+  --argjson target "$target_b" \
+  --argjson pinned "$(pinned_names | jq -R . | jq -s 'unique')" \
+  --arg match "$MATCH_RE" \
+  '{inventory:$inv, sizes:$sizes, total:$total, target:$target, pinned:$pinned, match:$match}' \
+  | jq "$SELECT_JQ")"
+
+n_evict="$(jq '.evict | length' <<<"$plan")"
+if [ "$n_evict" -eq 0 ]; then
+  echo "! No eviction candidates (all matching variants pinned, or none match --match)." >&2
+  exit 1
+fi
+
+echo
+echo "Proposed evictions (least recently used first):"
+jq -r '.evict[] | [.name, (.last_used | todate), .reclaim] | @tsv' <<<"$plan" \
+  | while IFS=$'\t' read -r n t r; do
+      printf '  %-60s last used %s  reclaims %s\n' "$n" "$t" "$(human "$r")"
+    done
+reclaim_b="$(jq '.reclaim' <<<"$plan")"
+after_b="$(jq '.after' <<<"$plan")"
+echo "Reclaim  : $(human "$reclaim_b") -> store $(human "$after_b")"
+satisfied="$(jq -r '.satisfied' <<<"$plan")"
+if [ "$satisfied" != "true" ]; then
+  echo "! Still over budget after evicting every candidate; the remaining blobs belong to" >&2
+  echo "  pinned/non-matching models (widen --match or raise --limit)." >&2
+fi
+
+if [ "$EXECUTE" -ne 1 ]; then
+  echo
+  echo "Nothing was deleted. Re-run with --execute to remove the variants above."
+  [ "$satisfied" = "true" ] && exit 0 || exit 1
+fi
+
+# Deleting every candidate and still ending over budget is worse than doing nothing
+if [ "$satisfied" != "true" ] && [ "$ALLOW_PARTIAL" -ne 1 ]; then
+  echo
+  echo "ERROR: refusing to delete: the proposal cannot get under budget. Nothing was deleted." >&2
+  echo "       re-run with --allow-partial to evict the candidates anyway" >&2
+  exit 1
+fi
+
+failed=0
+while IFS= read -r name; do
+  if curl -fsS --max-time 30 -X DELETE -H 'Content-Type: application/json' \
+       -d "$(jq -cn --arg m "$name" '{model:$m}')" "http://${HOST}/api/delete" >/dev/null 2>&1; then
+    echo "  - removed: $name"
+  else
+    echo "  - failed : $name" >&2; failed=$((failed+1))
+  fi
+done < <(jq -r '.evict[].name' <<<"$plan")
+
+echo "Store now: $(human "$(find "$STORE/blobs" -maxdepth 1 -type f -printf '%s\n' | awk '{s+=$1} END{print s+0}')")"
+[ "$failed" -eq 0 ] && [ "$satisfied" = "true" ] || exit 1
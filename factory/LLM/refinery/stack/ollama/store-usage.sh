@@ -0,0 +1,200 @@
+#!/usr/bin/env bash
+# store-usage.sh — per-variant usage tracking for an Ollama model store
+#   record --name NAME [--count N]   one event per served request (serving wrappers)
+#   record --from-ps [--host H:P]    sample /api/ps: every loaded model counts as used
+#   report [--days N] [--json]       last used, N-day requests/samples, never used
+#   last-used                        TSV name<TAB>epoch (consumed by store-quota.sh
+#                                    and cleanup-variants.sh); exit 3 without data
+#   compact                          roll events before today into one row per name and day
+# Events are appended as JSON lines to <store>/.fuze-usage.jsonl under an
+# exclusive flock, so the watchdog and manual/wrapper records can write
+# concurrently. Manifest mtimes are not used: ollama touches files on its own.
+# Once the file passes USAGE_COMPACT_BYTES a record compacts it under the same
+# lock (watchdog --record-usage samples every round); last use stays exact.
+
+set -euo pipefail
+
+. "$(cd "$(dirname "${BASH_SOURCE[0]}")/../common" && pwd)/store.sh"   # manifest_name, with_latest
+
+STORE="${OLLAMA_MODELS_DIR:-/FuZe/models/ollama}"
+HOST="${HOST:-127.0.0.1:11434}"
+USAGE_DB="${USAGE_DB:-}"                       # default: <store>/.fuze-usage.jsonl
+USAGE_COMPACT_BYTES="${USAGE_COMPACT_BYTES:-1048576}"  # compact on record past this size (0: never)
+DAYS=30
+NAME=""
+COUNT=1
+FROM_PS=0
+SOURCE="manual"
+JSON=0
+ACTION="${1:-}"
+
+usage(){
+  cat <<USAGE
+Usage:
+  $(basename "$0") record --name NAME [--count N] [--source TAG]
+  $(basename "$0") record --from-ps [--host HOST:PORT] [--source TAG]
+  $(basename "$0") report [--days N] [--json]
+  $(basename "$0") last-used
+  $(basename "$0") compact
+
+Options:
+  --store PATH      Ollama model store (default: $STORE)
+  --db FILE         Usage database (default: <store>/.fuze-usage.jsonl)
+  --name NAME       Variant that served a request
+  --count N         Requests represented by this record (default: 1)
+  --from-ps         Record every model currently loaded on --host
+  --host HOST:PORT  Daemon for --from-ps (default: $HOST)
+  --source TAG      Recorded with each event (default: manual, ps for --from-ps)
+  --days N          Report window (default: $DAYS)
+  --json            Report as JSON
+
+Env: USAGE_COMPACT_BYTES (record compacts the database past this size; default
+     $USAGE_COMPACT_BYTES, 0 = never)
+USAGE
+}
+
+case "$ACTION" in
+  record|report|last-used|compact) shift;;
+  -h|--help) usage; exit 0;;
+  *) echo "Unknown action: ${ACTION:-<none>}" >&2; usage; exit 2;;
+esac
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --store)   STORE="$2"; shift 2;;
+    --db)      USAGE_DB="$2"; shift 2;;
+    --name)    NAME="$2"; shift 2;;
+    --count)   COUNT="$2"; shift 2;;
+    --from-ps) FROM_PS=1; [ "$SOURCE" = "manual" ] && SOURCE="ps"; shift;;
+    --host)    HOST="$2"; shift 2;;
+    --source)  SOURCE="$2"; shift 2;;
+    --days)    DAYS="$2"; shift 2;;
+    --json)    JSON=1; shift;;
+    -h|--help) usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+USAGE_DB="${USAGE_DB:-${STORE%/}/.fuze-usage.jsonl}"
+
+need(){ command -v "$1" >/dev/null 2>&1 || { echo "Missing dependency: $1" >&2; exit 1; }; }
+need jq; need flock
+
+# ------------------------------------------------------------------------------
+# Helpers
+# ------------------------------------------------------------------------------
+# Events before today -> one row per (name, day): ts = last use that day,
+# count = requests, samples = /api/ps samples. Rollups roll up again unchanged.
+read -r -d '' COMPACT_JQ <<'JQ' || true
+  def samples: .samples // (if (.count // 0) == 0 then 1 else 0 end);
+  (map(select((.ts / 86400 | floor) < $today))
+   | group_by([.name, (.ts / 86400 | floor)])
+   | map({ts:(map(.ts) | max), name:.[0].name, count:(map(.count // 0) | add),
+          samples:(map(samples) | add), source:"rollup"})
+   | sort_by(.ts, .name))
+  + map(select((.ts / 86400 | floor) >= $today))
+  | .[]
+JQ
+
+compact_locked(){ # caller holds the exclusive db lock; rewrites in place (same inode)
+  local tmp
+  tmp="$(mktemp "${USAGE_DB}.XXXXXX")"
+  if jq -sc --argjson today "$(( $(date +%s) / 86400 ))" "$COMPACT_JQ" "$USAGE_DB" > "$tmp"; then
+    cat "$tmp" > "$USAGE_DB"
+  else
+    echo "! could not compact $USAGE_DB; left as is" >&2
+  fi
+  rm -f "$tmp"
+}
+
+append_events(){ # stdin: JSON lines -> appended atomically under the db lock
+  local fd lines
+  lines="$(cat)"
+  [ -n "$lines" ] || return 0
+  mkdir -p "$(dirname "$USAGE_DB")"
+  exec {fd}>>"$USAGE_DB"
+  flock -w 30 -x "$fd" || { echo "ERROR: could not lock $USAGE_DB" >&2; exit 1; }
+  printf '%s\n' "$lines" >&"$fd"
+  if [ "$USAGE_COMPACT_BYTES" -gt 0 ] && [ "$(stat -c '%s' "$USAGE_DB")" -gt "$USAGE_COMPACT_BYTES" ]; then
+    compact_locked
+  fi
+  exec {fd}>&-
+}
+
+read_events(){ # -> db contents under a shared lock (empty if absent)
+  [ -s "$USAGE_DB" ] || return 0
+  flock -w 30 -s "$USAGE_DB" cat "$USAGE_DB"
+}
+
+# ------------------------------------------------------------------------------
+# Actions
+# ------------------------------------------------------------------------------
+do_record(){
+  local now; now="$(date +%s)"
+  if [ "$FROM_PS" -eq 1 ]; then
+    need curl
+    curl -fsS --max-time 10 "http://${HOST}/api/ps" \
+      | jq -c --argjson t "$now" --arg s "$SOURCE" --arg h "$HOST" \
+          '.models[]? | {ts:$t, name:.name, count:0, source:$s, host:$h}' \
+      | append_events
+    return 0
+  fi
+  [ -n "$NAME" ] || { echo "ERROR: record needs --name or --from-ps" >&2; exit 2; }
+  [[ "$COUNT" =~ ^[0-9]+$ ]] || { echo "ERROR: --count must be an integer" >&2; exit 2; }
+  jq -cn --argjson t "$now" --arg n "$(with_latest "$NAME")" --argjson c "$COUNT" --arg s "$SOURCE" \
+    '{ts:$t, name:$n, count:$c, source:$s}' | append_events
+}
+
+do_last_used(){
+  [ -s "$USAGE_DB" ] || return 3
+  read_events | jq -sr 'group_by(.name)[] | [.[0].name, (map(.ts) | max)] | @tsv'
+}
+
+do_compact(){
+  local fd before
+  [ -s "$USAGE_DB" ] || { echo "Nothing to compact at $USAGE_DB"; return 0; }
+  before="$(wc -l < "$USAGE_DB" | tr -d ' ')"
+  exec {fd}>>"$USAGE_DB"
+  flock -w 30 -x "$fd" || { echo "ERROR: could not lock $USAGE_DB" >&2; exit 1; }
+  compact_locked
+  exec {fd}>&-
+  echo "Compacted $USAGE_DB: ${before} -> $(wc -l < "$USAGE_DB" | tr -d ' ') rows"
+}
+
+do_report(){
+  local now since models rep
+  now="$(date +%s)"; since=$((now - DAYS * 86400))
+  models="$(find "$STORE/manifests" -type f 2>/dev/null | sort | while IFS= read -r m; do
+      manifest_name "${m#"$STORE"/}"; done | jq -R . | jq -s '.')"
+  [ -s "$USAGE_DB" ] || echo "! No usage data yet at $USAGE_DB (record with: $(basename "$0") record ...)" >&2
+  rep="$(read_events | jq -s --argjson models "$models" --argjson since "$since" --argjson days "$DAYS" '
+    (group_by(.name) | map({key:.[0].name, value:{
+        last_used: (map(.ts) | max),
+        requests:  (map(select(.ts >= $since) | .count // 0) | add // 0),
+        samples:   (map(select(.ts >= $since) | .samples // (if (.count // 0) == 0 then 1 else 0 end)) | add // 0)}}) | from_entries) as $u
+    | {days:$days,
+       models: [ $models[] | {name:., last_used:($u[.].last_used // null),
+                              requests:($u[.].requests // 0), samples:($u[.].samples // 0)} ],
+       never_used: [ $models[] | select($u[.] == null) ],
+       gone: [ $u | keys[] | select(. as $n | $models | index($n) | not) ]}')"
+  if [ "$JSON" -eq 1 ]; then echo "$rep"; return 0; fi
+
+  echo "== ollama usage (${DAYS}d) : $STORE =="
+  printf '%-60s %-20s %9s %9s\n' "MODEL" "LAST USED" "REQUESTS" "SAMPLES"
+  jq -r '.models | sort_by(.last_used // 0) | reverse[]
+         | [.name, (if .last_used then (.last_used | strftime("%Y-%m-%d %H:%M")) else "never" end),
+            .requests, .samples] | @tsv' <<<"$rep" \
+    | while IFS=$'\t' read -r n t r s; do printf '%-60s %-20s %9s %9s\n' "$n" "$t" "$r" "$s"; done
+  echo
+  echo "Never used ($(jq '.never_used | length' <<<"$rep")):"
+  jq -r '.never_used[] | "  " + .' <<<"$rep"
+  if [ "$(jq '.gone | length' <<<"$rep")" -gt 0 ]; then
+    echo "Recorded but no longer in the store:"
+    jq -r '.gone[] | "  " + .' <<<"$rep"
+  fi
+}
+
+case "$ACTION" in
+  record)    do_record ;;
+  report)    do_report ;;
+  last-used) do_last_used ;;
+  compact)   do_compact ;;
+esac
@@ -0,0 +1,172 @@
+#!/usr/bin/env bash
+# store-verify.sh — verify an Ollama model store (blobs + manifests)
+# Copy mode (--src given): every source file must exist in --dst and match.
+#   fast : blob size (blobs are content-addressed; dedupes have their own
+#          mtime), manifests byte-for-byte
+#   full : blobs hashed (sha256) and compared against both the digest in the
+#          blob name (what manifests reference) and the source file's hash;
+#          manifests compared byte-for-byte
+# Audit mode (no --src): blobs in --dst checked against their own digest name
+# (full) or for non-zero size (fast).
+# Both modes also check that every digest referenced by a manifest has a blob.
+# Writes a TSV report (verdict, path, detail); exit 1 on any discrepancy.
+
+set -euo pipefail
+
+SRC=""
+DST=""
+MODE="${VERIFY:-full}"
+LIST=""
+REPORT=""
+JOBS="${VERIFY_JOBS:-$(nproc 2>/dev/null || echo 4)}"
+BUFSZ="${VERIFY_BUFSZ:-4M}"
+
+usage() {
+  cat <<USAGE
+Usage: $(basename "$0") --dst STORE [--src STORE] [options]
+  --dst PATH      Store to verify (destination of a migration)
+  --src PATH      Source store the files were copied/moved from (copy mode)
+  --mode MODE     full | fast (default: $MODE); fast compares blob size only, never
+                  mtime (a deduplicated blob keeps its own), manifests byte-for-byte
+  --list FILE     Only verify these paths (relative to the store, one per line)
+  --report FILE   TSV report path (default: ./store-verify-<ts>.tsv)
+  --jobs N        Parallel hashing workers (default: $JOBS, env VERIFY_JOBS)
+  --bufsz SIZE    Read buffer per hash, dd syntax (default: $BUFSZ, env VERIFY_BUFSZ)
+USAGE
+}
+
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --src) SRC="$2"; shift 2;;
+    --dst) DST="$2"; shift 2;;
+    --mode) MODE="$2"; shift 2;;
+    --list) LIST="$2"; shift 2;;
+    --report) REPORT="$2"; shift 2;;
+    --jobs) JOBS="$2"; shift 2;;
+    --bufsz) BUFSZ="$2"; shift 2;;
+    -h|--help) usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+
+[ -n "$DST" ] && [ -d "$DST" ] || { echo "ERROR: --dst store not found: ${DST:-<unset>}" >&2; exit 2; }
+[ -z "$SRC" ] || [ -d "$SRC" ] || { echo "ERROR: --src store not found: $SRC" >&2; exit 2; }
+case "$MODE" in full|fast) ;; *) echo "ERROR: --mode must be full or fast" >&2; exit 2;; esac
+[[ "$JOBS" =~ ^[1-9][0-9]*$ ]] || { echo "ERROR: --jobs must be a positive integer" >&2; exit 2; }
+REPORT="${REPORT:-./store-verify-$(date +%Y%m%d_%H%M%S).tsv}"
+
+need() { command -v "$1" >/dev/null 2>&1 || { echo "Missing dependency: $1" >&2; exit 1; }; }
+need sha256sum; need dd; need jq; need xargs; need stat
+
+# ---- Per-file check (runs in xargs workers) ----------------------------------
+hash_file() { dd if="$1" bs="$BUFSZ" status=none | sha256sum | awk '{print $1}'; }
+
+verify_one() { # rel path -> one TSV line on stdout
+  local rel="$1" d="$DST/$1" s="" bytes=0
+  [ -n "$SRC" ] && s="$SRC/$1"
+  if [ ! -f "$d" ]; then
+    printf 'MISSING\t%s\tnot in destination\t0\n' "$rel"; return 0
+  fi
+  bytes=$(stat -c '%s' "$d")
+  case "$rel" in
+    blobs/*)
+      local want="${rel#blobs/sha256-}" got src_hash
+      if [ "$MODE" = "fast" ]; then
+        # Blobs are content-addressed and dedupes keep their own mtime: size only
+        # (same inode when hardlinked); nothing is read, so bytes_read stays 0
+        if [ -n "$s" ] && [ ! "$s" -ef "$d" ]; then
+          if [ "$(stat -c '%s' "$s")" != "$bytes" ]; then
+            printf 'MISMATCH\t%s\tsize differs\t0\n' "$rel"; return 0
+          fi
+        elif [ "$bytes" -eq 0 ]; then
+          printf 'MISMATCH\t%s\tempty blob\t0\n' "$rel"; return 0
+        fi
+        printf 'OK\t%s\tfast\t0\n' "$rel"; return 0
+      fi
+      got="$(hash_file "$d")"
+      if [ "$want" != "$rel" ] && [ "$got" != "$want" ]; then
+        printf 'MISMATCH\t%s\tdigest %s != name %s\t%s\n' "$rel" "$got" "$want" "$bytes"; return 0
+      fi
+      if [ -n "$s" ]; then
+        src_hash="$(hash_file "$s")"
+        if [ "$src_hash" != "$got" ]; then
+          printf 'MISMATCH\t%s\tsource %s != destination %s\t%s\n' "$rel" "$src_hash" "$got" "$((bytes*2))"; return 0
+        fi
+        bytes=$((bytes*2))
+      fi
+      printf 'OK\t%s\tsha256 %s\t%s\n' "$rel" "$got" "$bytes" ;;
+    *)
+      # Manifests are small: byte-for-byte in both modes
+      if [ -n "$s" ] && [ ! "$s" -ef "$d" ]; then
+        cmp -s -- "$s" "$d" || { printf 'MISMATCH\t%s\tmanifest bytes differ\t%s\n' "$rel" "$((bytes*2))"; return 0; }
+      fi
+      printf 'OK\t%s\t%s\t0\n' "$rel" "$MODE" ;;
+  esac
+}
+export -f verify_one hash_file
+export SRC DST MODE BUFSZ
+
+# ---- Work list ---------------------------------------------------------------
+tmp_list="$(mktemp)"; tmp_out="$(mktemp)"
+cleanup() { rm -f "$tmp_list" "$tmp_out"; [ -n "${prog_pid:-}" ] && kill "$prog_pid" 2>/dev/null || true; }
+trap cleanup EXIT
+
+if [ -n "$LIST" ]; then
+  awk 'NF' "$LIST" > "$tmp_list"
+else
+  root="${SRC:-$DST}"
+  # A source may hold only blobs/ (e.g. a dedupe-only ALT): find's missing-dir status is not an error
+  (cd "$root" && { find blobs manifests -type f 2>/dev/null || true; } | sort) > "$tmp_list"
+fi
+TOTAL=$(wc -l < "$tmp_list" | tr -d ' ')
+
+echo "== store-verify ($MODE) =="
+echo "Destination : $DST"
+[ -n "$SRC" ] && echo "Source      : $SRC"
+echo "Files       : $TOTAL   workers=$JOBS bufsz=$BUFSZ"
+
+# ---- Progress ----------------------------------------------------------------
+start=$(date +%s)
+progress() {
+  while sleep 1; do
+    local n b el
+    n=$(wc -l < "$tmp_out" | tr -d ' ')
+    b=$(awk -F'\t' '{s+=$4} END{print s+0}' "$tmp_out")
+    el=$(( $(date +%s) - start )); [ "$el" -gt 0 ] || el=1
+    printf "\r   [verify] %d/%d (%d%%) %d MiB/s" "$n" "$TOTAL" "$(( TOTAL>0 ? n*100/TOTAL : 100 ))" "$(( b / el / 1048576 ))"
+  done
+}
+progress & prog_pid=$!
+
+tr '\n' '\0' < "$tmp_list" | xargs -0 -r -n 1 -P "$JOBS" bash -c 'verify_one "$1"' _ >> "$tmp_out"
+
+kill "$prog_pid" 2>/dev/null || true; wait "$prog_pid" 2>/dev/null || true; prog_pid=""
+
+# ---- Manifest references -----------------------------------------------------
+# Every config/layer digest named by a destination manifest must have a blob.
+while IFS= read -r rel; do
+  case "$rel" in manifests/*) ;; *) continue;; esac
+  [ -f "$DST/$rel" ] || continue
+  jq -r '[.config.digest?] + [.layers[]?.digest] | .[] | select(. != null)' "$DST/$rel" 2>/dev/null \
+    | while IFS= read -r dg; do
+        b="blobs/${dg/:/-}"
+        [ -f "$DST/$b" ] || printf 'MISSING_BLOB\t%s\treferenced by %s\t0\n' "$b" "$rel"
+      done
+done < "$tmp_list" >> "$tmp_out"
+
+# ---- Report ------------------------------------------------------------------
+elapsed=$(( $(date +%s) - start )); [ "$elapsed" -gt 0 ] || elapsed=1
+mkdir -p "$(dirname "$REPORT")"
+{ printf 'verdict\tpath\tdetail\tbytes_read\n'; sort -t$'\t' -k2,2 "$tmp_out"; } > "$REPORT"
+bad=$(awk -F'\t' '$1!="OK"' "$tmp_out" | wc -l | tr -d ' ')
+hashed=$(awk -F'\t' '{s+=$4} END{print s+0}' "$tmp_out")
+
+printf "\r   [verify] %d/%d done in %ds, %d MiB read (%d MiB/s)\n" \
+  "$TOTAL" "$TOTAL" "$elapsed" "$(( hashed / 1048576 ))" "$(( hashed / elapsed / 1048576 ))"
+echo "Report      : $REPORT"
+if [ "$bad" -gt 0 ]; then
+  echo "!! ${bad} discrepancies:" >&2
+  awk -F'\t' '$1!="OK"{printf "   %-12s %s  (%s)\n", $1, $2, $3}' "$tmp_out" | head -n 50 >&2
+  exit 1
+fi
+echo "✔ All files verified."
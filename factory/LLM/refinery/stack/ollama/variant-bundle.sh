@@ -0,0 +1,231 @@
+#!/usr/bin/env bash
+# variant-bundle.sh — move baked variants between boxes as portable bundles
+#   export <name> --out FILE.tar.zst   manifest + referenced blobs + index.json
+#   import FILE.tar.zst                verify digests, stage, atomically place
+# Imports stage under <store>/.import-staging/<bundle> (same filesystem as the
+# store), so an interrupted import resumes: verified staged files are kept and
+# only missing/corrupt ones are re-extracted. Blobs are moved in first and the
+# manifest last, so a variant never appears half-imported.
+
+set -euo pipefail
+
+STORE="${OLLAMA_MODELS_DIR:-/FuZe/models/ollama}"
+HOST="${HOST:-127.0.0.1:11434}"
+OLLAMA_BIN="${OLLAMA_BIN:-/usr/local/bin/ollama}"
+OUT=""
+CHECK_HOST=0
+. "$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)/store-lock.sh"
+. "$(cd "$(dirname "${BASH_SOURCE[0]}")/../common" && pwd)/store.sh"   # manifest_rel
+ACTION="${1:-}"
+
+usage() {
+  cat <<USAGE
+Usage:
+  $(basename "$0") export <name[:tag]> --out FILE.tar.zst [--store PATH]
+  $(basename "$0") import FILE.tar.zst [--store PATH] [--check-host]
+
+Options:
+  --store PATH     Ollama model store (default: $STORE)
+  --out FILE       Bundle to write (export)
+  --host HOST:PORT Daemon used for version info / --check-host (default: $HOST)
+  --check-host     After import, confirm the daemon lists the variant
+  --force-break-lock  Proceed without the store lock after the wait times out
+USAGE
+}
+
+case "$ACTION" in
+  export|import) shift;;
+  -h|--help) usage; exit 0;;
+  *) echo "Unknown action: ${ACTION:-<none>}" >&2; usage; exit 2;;
+esac
+TARGET="${1:-}"; [ $# -gt 0 ] && shift
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --store) STORE="$2"; shift 2;;
+    --out) OUT="$2"; shift 2;;
+    --host) HOST="$2"; shift 2;;
+    --check-host) CHECK_HOST=1; shift;;
+    --force-break-lock) FORCE_BREAK_LOCK=1; shift;;
+    -h|--help) usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+[ -n "$TARGET" ] || { usage; exit 2; }
+[ -d "$STORE" ] && STORE="$(cd "$STORE" && pwd)"
+
+need() { command -v "$1" >/dev/null 2>&1 || { echo "Missing dependency: $1" >&2; exit 1; }; }
+need jq; need tar; need zstd; need sha256sum
+
+# Export temp dir / partial bundle, removed on any exit (store_lock chains onto this trap)
+WORK=""; PART=""
+trap '[ -z "$WORK" ] || rm -rf "$WORK"; [ -z "$PART" ] || rm -f "$PART"' EXIT
+
+###############################################################################
+# Helpers
+###############################################################################
+blob_rels() { # manifest file -> blobs/sha256-... per referenced digest
+  jq -r '[.config.digest?] + [.layers[]?.digest] | .[] | select(. != null)' "$1" \
+    | sed -E 's#^sha256:#blobs/sha256-#' | sort -u
+}
+
+sha_of() { sha256sum "$1" | awk '{print $1}'; }
+
+# index.json is untrusted input: paths go to mv/tar, so only the two shapes export writes
+valid_index() { # index.json -> 0 if every path is manifests/... (no ..) or a blob named by its digest
+  jq -e '
+    (.manifest.path | type == "string" and test("^manifests(/[^/\\\\[:cntrl:]]+)+$")
+                    and (test("(^|/)\\.\\.?(/|$)") | not))
+    and (.manifest.sha256 | type == "string" and test("^[0-9a-f]{64}$"))
+    and (.blobs | type == "array")
+    and all(.blobs[]; (.path | type == "string" and test("^blobs/sha256-[0-9a-f]{64}$"))
+                      and .sha256 == (.path | ltrimstr("blobs/sha256-")))' "$1" >/dev/null 2>&1
+}
+
+daemon_version() {
+  curl -fsS --max-time 5 "http://${HOST}/api/version" 2>/dev/null | jq -r '.version // empty' 2>/dev/null \
+    || "$OLLAMA_BIN" --version 2>/dev/null | awk '{print $NF}' || true
+}
+
+###############################################################################
+# export
+###############################################################################
+do_export() {
+  local name="$1" mrel work bytes=0 rel
+  [ -n "$OUT" ] || { echo "ERROR: export requires --out FILE" >&2; exit 2; }
+  mrel="$(manifest_rel "$name")"
+  [ -f "$STORE/$mrel" ] || { echo "ERROR: manifest not found: $STORE/$mrel" >&2; exit 1; }
+  store_lock "$STORE" shared   # blobs must not be cleaned up mid-export
+
+  WORK="$(mktemp -d)"; work="$WORK"
+  {
+    echo "$mrel"
+    blob_rels "$STORE/$mrel"
+  } > "$work/files.lst"
+
+  while IFS= read -r rel; do
+    [ -f "$STORE/$rel" ] || { echo "ERROR: referenced blob missing: $STORE/$rel" >&2; exit 1; }
+    bytes=$((bytes + $(stat -c '%s' "$STORE/$rel")))
+  done < "$work/files.lst"
+
+  echo "== export $name =="
+  echo "Manifest : $mrel"
+  echo "Files    : $(wc -l < "$work/files.lst" | tr -d ' ') ($((bytes / 1048576)) MiB)"
+
+  # Index: blob digests come from their content-addressed names, the manifest is hashed
+  jq -n --arg name "$name" --arg manifest "$mrel" --arg ver "$(daemon_version)" \
+        --arg host "$(hostname -s 2>/dev/null || hostname)" --arg ts "$(date -Is)" \
+        --arg msha "$(sha_of "$STORE/$mrel")" --argjson msize "$(stat -c '%s' "$STORE/$mrel")" \
+        --argjson blobs "$(while IFS= read -r rel; do
+            case "$rel" in blobs/*) jq -cn --arg p "$rel" --arg d "${rel#blobs/sha256-}" \
+              --argjson s "$(stat -c '%s' "$STORE/$rel")" '{path:$p, sha256:$d, size:$s}';; esac
+          done < "$work/files.lst" | jq -s '.')" \
+    '{format:1, name:$name, created:$ts, source_host:$host, ollama_version:$ver,
+      manifest:{path:$manifest, sha256:$msha, size:$msize}, blobs:$blobs}' > "$work/index.json"
+
+  mkdir -p "$(dirname "$OUT")"
+  # index.json first so import can read it without scanning the whole archive
+  PART="$OUT.part"
+  tar -cf - -C "$work" index.json -C "$STORE" -T "$work/files.lst" \
+    | zstd -T0 -q -f -o "$PART"
+  mv -f "$PART" "$OUT"; PART=""
+  echo "✔ Wrote $OUT ($(( $(stat -c '%s' "$OUT") / 1048576 )) MiB)"
+}
+
+###############################################################################
+# import
+###############################################################################
+drop_stage() { # stage dir (and the staging root once empty)
+  rm -rf "$1"
+  rmdir "$STORE/.import-staging" 2>/dev/null || true
+}
+
+verify_staged() { # stage rel expected_sha -> 0 if staged file matches
+  [ -f "$1/$2" ] && [ "$(sha_of "$1/$2")" = "$3" ]
+}
+
+do_import() {
+  local bundle="$1" stage index name mrel msha rel sha missing
+  [ -f "$bundle" ] || { echo "ERROR: bundle not found: $bundle" >&2; exit 2; }
+  mkdir -p "$STORE"
+  store_lock "$STORE" exclusive
+  stage="$STORE/.import-staging/$(basename "$bundle" | sed -E 's/\.tar\.zst$//')"
+  mkdir -p "$stage"
+
+  zstd -dc "$bundle" | tar -C "$stage" -xf - --occurrence=1 index.json 2>/dev/null || true
+  index="$stage/index.json"
+  [ -s "$index" ] || { echo "ERROR: bundle has no index.json" >&2; drop_stage "$stage"; exit 1; }
+  valid_index "$index" || { echo "ERROR: bundle index.json has unsafe or malformed paths; refusing" >&2; drop_stage "$stage"; exit 1; }
+  name="$(jq -r '.name' "$index")"; mrel="$(jq -r '.manifest.path' "$index")"; msha="$(jq -r '.manifest.sha256' "$index")"
+
+  echo "== import $name =="
+  echo "Bundle   : $bundle (from $(jq -r '.source_host' "$index"), ollama $(jq -r '.ollama_version' "$index"))"
+  echo "Store    : $STORE"
+
+  # Refuse to clobber a different variant with the same name
+  if [ -f "$STORE/$mrel" ]; then
+    if [ "$(sha_of "$STORE/$mrel")" = "$msha" ]; then
+      echo "✔ $name already present with identical manifest — nothing to do."
+      drop_stage "$stage"; return 0
+    fi
+    echo "ERROR: $name exists in $STORE with a different manifest; remove it first." >&2
+    drop_stage "$stage"
+    exit 1
+  fi
+
+  # Resume: keep verified staged files, re-extract only what's missing/corrupt
+  missing="$stage/.missing.lst"; : > "$missing"
+  verify_staged "$stage" "$mrel" "$msha" || { rm -f "$stage/$mrel"; echo "$mrel" >> "$missing"; }
+  while IFS=$'\t' read -r rel sha; do
+    if [ -f "$STORE/$rel" ]; then continue; fi          # blob already in store
+    verify_staged "$stage" "$rel" "$sha" && continue
+    rm -f "$stage/$rel"; echo "$rel" >> "$missing"
+  done < <(jq -r '.blobs[] | [.path, .sha256] | @tsv' "$index")
+
+  if [ -s "$missing" ]; then
+    echo "Extract  : $(wc -l < "$missing" | tr -d ' ') files"
+    zstd -dc "$bundle" | tar -C "$stage" -xf - -T "$missing"
+  else
+    echo "Extract  : nothing (all staged files verified)"
+  fi
+
+  # Verify every staged file before touching the store
+  verify_staged "$stage" "$mrel" "$msha" || { echo "ERROR: manifest digest mismatch in bundle" >&2; exit 1; }
+  # Every blob the manifest references must come with the bundle or already be in the store
+  missing="$(blob_rels "$stage/$mrel" | while IFS= read -r rel; do
+      [ -f "$STORE/$rel" ] || jq -e --arg p "$rel" 'any(.blobs[]; .path == $p)' "$index" >/dev/null || echo "$rel"
+    done)"
+  if [ -n "$missing" ]; then
+    echo "ERROR: bundle lacks $(wc -l <<<"$missing" | tr -d ' ') blob(s) the manifest references (not in $STORE either); refusing" >&2
+    sed 's/^/       /' <<<"$missing" >&2
+    drop_stage "$stage"
+    exit 1
+  fi
+  while IFS=$'\t' read -r rel sha; do
+    [ -f "$STORE/$rel" ] && continue
+    verify_staged "$stage" "$rel" "$sha" || { echo "ERROR: digest mismatch for $rel (staging kept at $stage)" >&2; exit 1; }
+  done < <(jq -r '.blobs[] | [.path, .sha256] | @tsv' "$index")
+
+  # Place: blobs first (rename within the same filesystem), manifest last
+  mkdir -p "$STORE/blobs" "$(dirname "$STORE/$mrel")"
+  while IFS= read -r rel; do
+    [ -f "$STORE/$rel" ] || mv -f "$stage/$rel" "$STORE/$rel"
+  done < <(jq -r '.blobs[].path' "$index")
+  mv -f "$stage/$mrel" "$STORE/$mrel"
+  chown -R --reference="$STORE" "$STORE/blobs" "$STORE/manifests" 2>/dev/null || true
+  drop_stage "$stage"
+  echo "✔ Imported $name"
+
+  if [ "$CHECK_HOST" -eq 1 ]; then
+    local full; case "$name" in *:*) full="$name";; *) full="$name:latest";; esac
+    if curl -fsS --max-time 10 "http://${HOST}/api/tags" | jq -r '.models[]?.name' | grep -Fxq "$full"; then
+      echo "✔ ${HOST} lists $full"
+    else
+      echo "! ${HOST} does not list $full yet (restart the daemon if it caches its model list)" >&2
+    fi
+  fi
+}
+
+case "$ACTION" in
+  export) do_export "$TARGET" ;;
+  import) do_import "$TARGET" ;;
+esac
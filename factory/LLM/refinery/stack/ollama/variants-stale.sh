@@ -0,0 +1,150 @@
+#!/usr/bin/env bash
+# variants-stale.sh — find baked variants whose base model has since changed
+# A variant is stale when the base it was baked from now resolves to a different
+# manifest digest in the store (e.g. `ollama pull` brought a new gemma3:4b).
+#   recorded : bake-modelfile.sh --create logs base + base digest to
+#              <store>/.fuze-bakes.jsonl; compared against the base's current
+#              manifest sha256
+#   inferred : no record — the variant shares model weight layers with some
+#              non-variant model (current), or it doesn't but a model with its
+#              base alias exists (stale, inferred). Quantized variants never
+#              share weights, so only recorded staleness is acted on by default.
+# Variants whose base is no longer in the store at all are reported as orphaned.
+# common/preflight.sh warns when --count exceeds STALE_WARN_THRESHOLD (default 0).
+
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+. "${SCRIPT_DIR}/../common/naming.sh"
+. "${SCRIPT_DIR}/../common/store.sh"
+
+STORE="${OLLAMA_MODELS_DIR:-/FuZe/models/ollama}"
+BAKES_DB="${BAKES_DB:-}"                       # default: <store>/.fuze-bakes.jsonl
+ALIAS_PREFIX="${ALIAS_PREFIX:-LLM-FuZe-}"
+ALIAS_SUFFIX="${ALIAS_SUFFIX:-}"
+MATCH_RE='.*-nvidia-.*-ng[0-9]+(:[[:alnum:]._-]+)?$'  # same default as cleanup-variants.sh
+OUTPUT="table"                                 # table | json | names | count
+INFERRED=0
+
+usage(){
+  cat <<USAGE
+Usage: $(basename "$0") [options]
+
+Options:
+  --store PATH        Ollama model store (default: $STORE)
+  --bakes FILE        Bake records (default: <store>/.fuze-bakes.jsonl)
+  --match REGEX       Which names are variants (default: $MATCH_RE)
+  --include-inferred  Treat inferred staleness like recorded for --names/--count
+  --json              Full report as JSON
+  --names             Stale variant names only, one per line (for cleanup-variants.sh --stale)
+  --count             Number of stale variants only
+  -h|--help           This help
+USAGE
+}
+
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --store)   STORE="$2"; shift 2;;
+    --bakes)   BAKES_DB="$2"; shift 2;;
+    --match)   MATCH_RE="$2"; shift 2;;
+    --include-inferred) INFERRED=1; shift;;
+    --json)    OUTPUT="json"; shift;;
+    --names)   OUTPUT="names"; shift;;
+    --count)   OUTPUT="count"; shift;;
+    -h|--help) usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+BAKES_DB="${BAKES_DB:-${STORE%/}/.fuze-bakes.jsonl}"
+
+need(){ command -v "$1" >/dev/null 2>&1 || { echo "Missing dependency: $1" >&2; exit 1; }; }
+need jq; need sha256sum; need find
+
+[ -d "$STORE/manifests" ] || { echo "ERROR: not an ollama store (no manifests/): $STORE" >&2; exit 2; }
+
+# ------------------------------------------------------------------------------
+# Helpers
+# ------------------------------------------------------------------------------
+inventory(){ # -> JSON lines {name, aliases:[...], digest, mtime, weights:[...], blobs:{digest:size}}
+  local m rel n
+  while IFS= read -r m; do
+    rel="${m#"$STORE"/}"; n="$(manifest_name "$rel")"
+    # base_alias of the name, and of the name without :latest (benchmark.sh names either)
+    jq -c --arg n "$n" --arg a "$(base_alias "$n")" --arg b "$(base_alias "${n%:latest}")" \
+          --arg d "sha256:$(sha256sum "$m" | awk '{print $1}')" --argjson t "$(stat -c '%Y' "$m")" \
+      '{name:$n, aliases:([$a, $b] | unique), digest:$d, mtime:$t,
+        weights:[.layers[]? | select(.mediaType? // "" | test("image\\.model$")) | .digest],
+        blobs:([.config?, .layers[]?] | map(select(.digest? != null) | {(.digest): (.size // 0)}) | add // {})}' \
+      "$m" 2>/dev/null || echo "! skipping unreadable manifest: $rel" >&2
+  done < <(find "$STORE/manifests" -type f 2>/dev/null | sort)
+}
+
+# Classification (pure: inventory + bake records -> report)
+read -r -d '' CLASSIFY_JQ <<'JQ' || true
+  def latest: if test(":") then . else . + ":latest" end;
+  def variant_alias: sub(":[^:]*$"; "") | ltrimstr($prefix)
+                     | sub("-nvidia-[^-]+-ng[0-9]+$"; "") | sub("-ctx[0-9]+$"; "")   # bake-sweep.sh num_ctx sweeps
+                     | (if $suffix != "" then rtrimstr($suffix) else . end);
+
+  (reduce $bakes[] as $b ({}; .[$b.variant | latest] = $b)) as $rec
+  | ($inv | map(select(.name | test($match)))) as $variants
+  # Bases: neither a variant nor one of our own names (malformed/double-prefixed bakes)
+  | ($inv | map(select((.name | test($match) | not) and ($prefix == "" or (.name | startswith($prefix) | not))))) as $bases
+  | (reduce $inv[] as $m ({}; reduce ($m.blobs | keys[]) as $d (.; .[$d] += 1))) as $refs
+  | [ $variants[] as $v
+      | ($v.blobs | to_entries | map(select($refs[.key] == 1) | .value) | add // 0) as $own
+      | ($rec[$v.name]) as $r
+      | ($v.name | variant_alias) as $va
+      | if $r != null then
+          ($r.base | latest) as $b
+          | ($bases | map(select(.name == $b)) | first) as $cur
+          | if $cur == null then {status:"orphaned", how:"recorded", base:$b, detail:"base not in store"}
+            elif $cur.digest == $r.base_digest then {status:"current", how:"recorded", base:$b, detail:""}
+            else {status:"stale", how:"recorded", base:$b,
+                  detail:"baked from \($r.base_digest[7:19]), base now \($cur.digest[7:19])"} end
+          | . + {age_from:($r.ts // $v.mtime)}
+        else
+          ($bases | map(select(.weights as $w | $v.weights | any(. as $x | $w | index($x)))) | map(.name) | sort) as $sharing
+          | ($bases | map(select(.aliases | index($va))) | map(.name) | sort) as $exact
+          # a create-time quant tag (bake-modelfile.sh --quantize) is not part of the base alias
+          | (if ($exact | length) > 0 then $exact
+             else $bases | map(select(.aliases | index($va | sub("-q[0-9][A-Za-z0-9_.]*$"; "")))) | map(.name) | sort end) as $named
+          # prefer a weight-sharing model whose alias also matches (sharing ∩ named)
+          | if ($sharing | length) > 0 then
+              {status:"current", how:"inferred", detail:"shares weights",
+               base:((($sharing - ($sharing - $named)) + $sharing)[0])}
+            elif ($named | length) > 0 then {status:"stale", how:"inferred", base:$named[0], detail:"no shared weights with current base"}
+            else {status:"orphaned", how:"inferred", base:null, detail:"no model with alias \($va)"} end
+          | . + {age_from:$v.mtime}
+        end
+      | {name:$v.name, status, how, base, detail, age_days:((($now - .age_from) / 86400) | floor), own_bytes:$own} ]
+  | sort_by(.status, .name)
+JQ
+
+# ------------------------------------------------------------------------------
+# Main
+# ------------------------------------------------------------------------------
+report="$(jq -n \
+  --argjson inv "$(inventory | jq -s '.')" \
+  --argjson bakes "$( [ -s "$BAKES_DB" ] && jq -sc '.' "$BAKES_DB" || echo '[]')" \
+  --argjson now "$(date +%s)" \
+  --arg match "$MATCH_RE" --arg prefix "$ALIAS_PREFIX" --arg suffix "$ALIAS_SUFFIX" \
+  "$CLASSIFY_JQ")"
+
+acted='select(.status == "stale" and (.how == "recorded" or $inferred == 1))'
+case "$OUTPUT" in
+  json)  echo "$report" ;;
+  names) jq -r --argjson inferred "$INFERRED" ".[] | $acted | .name" <<<"$report" ;;
+  count) jq --argjson inferred "$INFERRED" "[.[] | $acted] | length" <<<"$report" ;;
+  table)
+    [ -s "$BAKES_DB" ] || echo "! No bake records at $BAKES_DB; staleness is inferred from shared weight layers" >&2
+    echo "== stale variants : $STORE =="
+    printf '%-52s %-9s %-9s %6s %10s  %s\n' "VARIANT" "STATUS" "HOW" "AGE_D" "OWN_MiB" "BASE / DETAIL"
+    jq -r '.[] | [.name, .status, .how, .age_days, (.own_bytes / 1048576 | floor), (.base // "-"), .detail] | @tsv' <<<"$report" \
+      | while IFS=$'\t' read -r n s h a b base d; do
+          printf '%-52s %-9s %-9s %6s %10s  %s %s\n' "$n" "$s" "$h" "$a" "$b" "$base" "${d:+($d)}"
+        done
+    echo
+    echo "Stale: $(jq '[.[] | select(.status=="stale")] | length' <<<"$report") ($(jq '[.[] | select(.status=="stale" and .how=="inferred")] | length' <<<"$report") inferred), orphaned: $(jq '[.[] | select(.status=="orphaned")] | length' <<<"$report")"
+    ;;
+esac
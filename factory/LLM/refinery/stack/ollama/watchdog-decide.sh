@@ -0,0 +1,49 @@
+#!/usr/bin/env bash
+# watchdog-decide.sh — watchdog.sh restart policy (source this file)
+# Pure functions: every input, the clock included, is an argument, so the
+# policy can be replayed on a simulated clock (tests/watchdog.test.sh).
+# Reads FAIL_THRESHOLD, BACKOFF_BASE, BACKOFF_MAX, MAX_RESTARTS_PER_HOUR.
+
+# Restart timestamps within the last hour from a comma list
+recent_restarts(){ # now csv -> csv
+  local now="$1" out="" t
+  for t in ${2//,/ }; do
+    if [ $((now - t)) -lt 3600 ]; then out="${out:+$out,}$t"; fi
+  done
+  echo "$out"
+}
+
+count_csv(){ [ -n "$1" ] && echo $(( $(tr -cd ',' <<<"$1" | wc -c) + 1 )) || echo 0; }
+
+# Backoff after the k-th recent restart: BASE * 2^(k-1), capped
+backoff_secs(){ # recent_count
+  local k="$1" b="$BACKOFF_BASE"
+  [ "$k" -gt 0 ] || { echo 0; return; }
+  while [ "$k" -gt 1 ] && [ "$b" -lt "$BACKOFF_MAX" ]; do b=$((b*2)); k=$((k-1)); done
+  [ "$b" -gt "$BACKOFF_MAX" ] && b="$BACKOFF_MAX"
+  echo "$b"
+}
+
+# decide: ok | wait | restart | backoff | capped
+decide(){ # now probe_ok fails recent_csv
+  local now="$1" probe_ok="$2" fails="$3" recent="$4" n last
+  [ "$probe_ok" -eq 1 ] && { echo ok; return; }
+  [ "$fails" -ge "$FAIL_THRESHOLD" ] || { echo wait; return; }
+  n="$(count_csv "$recent")"
+  [ "$n" -ge "$MAX_RESTARTS_PER_HOUR" ] && { echo capped; return; }
+  if [ "$n" -gt 0 ]; then
+    last="${recent##*,}"
+    [ $((now - last)) -lt "$(backoff_secs "$n")" ] && { echo backoff; return; }
+  fi
+  echo restart
+}
+
+# Webhook only on state changes; per-round repeats stay in the JSONL log
+should_notify(){ # action prev_action fails -> 0 to notify
+  case "$1" in
+    probe_failed)                       [ "$3" -eq 1 ] ;;
+    restarted|restart_failed|recovered) return 0 ;;
+    capped|restart_dry_run)             [ "$1" != "$2" ] ;;
+    *)                                  return 1 ;;
+  esac
+}
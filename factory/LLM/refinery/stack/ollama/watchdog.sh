@@ -0,0 +1,221 @@
+#!/usr/bin/env bash
+# watchdog.sh — restart wedged Ollama instances (process alive, API dead)
+# Probes each instance's /api/version (and optionally a tiny generation),
+# restarts its systemd unit after N consecutive failures with exponential
+# backoff and a max-restarts-per-hour cap. Failure counts and restart history
+# persist in STATE_DIR so a watchdog restart doesn't reset them.
+# Every action is logged as JSON; state changes (first failure, restart, restart
+# failure, entering the cap, recovery) are also POSTed to NOTIFY_WEBHOOK.
+
+set -euo pipefail
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+
+# ------------------------------------------------------------------------------
+# Config (override via env / flags)
+# ------------------------------------------------------------------------------
+LOG_DIR="${LOG_DIR:-/var/log/fuze-stack}"
+STATE_DIR="${STATE_DIR:-/var/lib/fuze-stack/watchdog}"
+# Space-separated UNIT=HOST:PORT pairs (default :11434 unit: ollama-persist.service,
+# or the stock ollama.service when only that one is enabled)
+INSTANCES="${WATCH_INSTANCES:-}"
+INTERVAL="${WATCH_INTERVAL:-30}"              # seconds between probe rounds
+PROBE_TIMEOUT="${PROBE_TIMEOUT:-5}"           # /api/version timeout
+PROBE_MODEL="${PROBE_MODEL:-}"                # optional: model for a tiny generation
+PROBE_GEN_TIMEOUT="${PROBE_GEN_TIMEOUT:-60}"
+FAIL_THRESHOLD="${FAIL_THRESHOLD:-3}"         # consecutive failures before restart
+BACKOFF_BASE="${BACKOFF_BASE:-30}"            # seconds; doubles per recent restart
+BACKOFF_MAX="${BACKOFF_MAX:-900}"
+MAX_RESTARTS_PER_HOUR="${MAX_RESTARTS_PER_HOUR:-4}"
+NOTIFY_WEBHOOK="${NOTIFY_WEBHOOK:-}"
+USAGE_RECORD="${USAGE_RECORD:-0}"              # 1: sample /api/ps into store-usage.sh each round
+ONCE=0
+DRY_RUN=0
+INSTALL_UNIT=0
+UNIT_NAME="fuze-ollama-watchdog.service"
+
+usage(){
+  cat <<USAGE
+Usage: $(basename "$0") [options]
+  --instance UNIT=HOST:PORT  Instance to watch (repeatable; replaces defaults)
+  --interval SECS            Seconds between probe rounds (default: $INTERVAL)
+  --probe-model NAME         Also require a tiny generation from NAME
+  --threshold N              Consecutive failures before restart (default: $FAIL_THRESHOLD)
+  --max-restarts N           Max restarts per unit per hour (default: $MAX_RESTARTS_PER_HOUR)
+  --once                     Run a single probe round and exit
+  --dry-run                  Log decisions but never restart
+  --record-usage             Record loaded models (/api/ps) via store-usage.sh (compacted daily)
+  --install-unit             Write and enable the ${UNIT_NAME} systemd unit
+  -h|--help                  This help
+
+Env: WATCH_INSTANCES, WATCH_INTERVAL, PROBE_TIMEOUT, PROBE_MODEL, PROBE_GEN_TIMEOUT, FAIL_THRESHOLD,
+     BACKOFF_BASE, BACKOFF_MAX, MAX_RESTARTS_PER_HOUR, STATE_DIR, LOG_DIR, NOTIFY_WEBHOOK,
+     USAGE_RECORD
+USAGE
+}
+
+custom_instances=""
+while [ $# -gt 0 ]; do
+  case "$1" in
+    --instance)     custom_instances="${custom_instances:+$custom_instances }$2"; shift 2;;
+    --interval)     INTERVAL="$2"; shift 2;;
+    --probe-model)  PROBE_MODEL="$2"; shift 2;;
+    --threshold)    FAIL_THRESHOLD="$2"; shift 2;;
+    --max-restarts) MAX_RESTARTS_PER_HOUR="$2"; shift 2;;
+    --once)         ONCE=1; shift;;
+    --dry-run)      DRY_RUN=1; shift;;
+    --record-usage) USAGE_RECORD=1; shift;;
+    --install-unit) INSTALL_UNIT=1; shift;;
+    -h|--help)      usage; exit 0;;
+    *) echo "Unknown arg: $1" >&2; usage; exit 2;;
+  esac
+done
+[ -n "$custom_instances" ] && INSTANCES="$custom_instances"
+
+need(){ command -v "$1" >/dev/null 2>&1 || { echo "Missing dependency: $1" >&2; exit 1; }; }
+need curl; need jq; need systemctl
+
+if [ -z "$INSTANCES" ]; then
+  persist_unit="ollama-persist.service"
+  if ! systemctl is-enabled "$persist_unit" >/dev/null 2>&1 && systemctl is-enabled ollama.service >/dev/null 2>&1; then
+    persist_unit="ollama.service"
+  fi
+  INSTANCES="${persist_unit}=127.0.0.1:11434 ollama-test-a.service=127.0.0.1:11435 ollama-test-b.service=127.0.0.1:11436"
+fi
+
+# ------------------------------------------------------------------------------
+# Unit generator (same layout as the ollama units in benchmark.sh)
+# ------------------------------------------------------------------------------
+if [ "$INSTALL_UNIT" -eq 1 ]; then
+  self="${SCRIPT_DIR}/$(basename "$0")"
+  cat >/etc/systemd/system/"$UNIT_NAME" <<UNIT
+[Unit]
+Description=FuZe Ollama watchdog
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+Environment="WATCH_INSTANCES=${INSTANCES}"
+Environment=WATCH_INTERVAL=${INTERVAL}
+Environment=PROBE_TIMEOUT=${PROBE_TIMEOUT}
+Environment=PROBE_MODEL=${PROBE_MODEL}
+Environment=PROBE_GEN_TIMEOUT=${PROBE_GEN_TIMEOUT}
+Environment=FAIL_THRESHOLD=${FAIL_THRESHOLD}
+Environment=BACKOFF_BASE=${BACKOFF_BASE}
+Environment=BACKOFF_MAX=${BACKOFF_MAX}
+Environment=MAX_RESTARTS_PER_HOUR=${MAX_RESTARTS_PER_HOUR}
+Environment=STATE_DIR=${STATE_DIR}
+Environment=LOG_DIR=${LOG_DIR}
+Environment=NOTIFY_WEBHOOK=${NOTIFY_WEBHOOK}
+Environment=USAGE_RECORD=${USAGE_RECORD}
+ExecStart=${self}
+Restart=always
+RestartSec=5
+
+[Install]
+WantedBy=multi-user.target
+UNIT
+  systemctl daemon-reload
+  systemctl enable --now "$UNIT_NAME"
+  echo "Installed and started ${UNIT_NAME}"
+  exit 0
+fi
+
+mkdir -p "$LOG_DIR" "$STATE_DIR"
+EVENT_LOG="${LOG_DIR}/ollama_watchdog.jsonl"
+
+# Restart policy: recent_restarts, count_csv, backoff_secs, decide, should_notify (pure; tested
+# on a simulated clock in tests/watchdog.test.sh)
+. "${SCRIPT_DIR}/watchdog-decide.sh"
+
+# ------------------------------------------------------------------------------
+# I/O: probes, state, logging
+# ------------------------------------------------------------------------------
+probe(){ # ep -> 0 healthy
+  local ep="$1" payload
+  curl -fsS --max-time "$PROBE_TIMEOUT" "http://${ep}/api/version" >/dev/null 2>&1 || return 1
+  [ -n "$PROBE_MODEL" ] || return 0
+  payload="$(jq -cn --arg m "$PROBE_MODEL" '{model:$m, prompt:"ping", stream:false, options:{num_predict:1}}')"
+  curl -fsS --max-time "$PROBE_GEN_TIMEOUT" -H 'Content-Type: application/json' -d "$payload" \
+    "http://${ep}/api/generate" 2>/dev/null | jq -e '.done == true' >/dev/null 2>&1
+}
+
+state_file(){ echo "${STATE_DIR}/${1//\//_}.state"; }
+
+load_state(){ # unit -> sets FAILS RESTARTS LAST
+  FAILS=0; RESTARTS=""; LAST=""
+  local f; f="$(state_file "$1")"
+  [ -f "$f" ] || return 0
+  FAILS="$(sed -nE 's/^fails=([0-9]+)$/\1/p' "$f")"; FAILS="${FAILS:-0}"
+  RESTARTS="$(sed -nE 's/^restarts=([0-9,]*)$/\1/p' "$f")"
+  LAST="$(sed -nE 's/^last=([a-z_]*)$/\1/p' "$f")"
+}
+
+save_state(){ # unit
+  local f tmp; f="$(state_file "$1")"; tmp="${f}.tmp"
+  printf 'fails=%s\nrestarts=%s\nlast=%s\n' "$FAILS" "$RESTARTS" "$LAST" > "$tmp" && mv -f "$tmp" "$f"
+}
+
+event(){ # unit ep action detail (LAST: previous action for this unit)
+  local ev prev="${LAST:-}"
+  ev="$(jq -cn --arg ts "$(date -Is)" --arg u "$1" --arg ep "$2" --arg a "$3" --arg d "$4" \
+        --argjson f "$FAILS" '{ts:$ts, unit:$u, endpoint:$ep, action:$a, fails:$f, detail:$d}')"
+  echo "$ev" | tee -a "$EVENT_LOG"
+  LAST="$3"
+  if [ -n "$NOTIFY_WEBHOOK" ] && should_notify "$3" "$prev" "$FAILS"; then
+    curl -fsS --max-time 10 -H 'Content-Type: application/json' -d "$ev" "$NOTIFY_WEBHOOK" >/dev/null 2>&1 || true
+  fi
+}
+
+declare -A SKIP_LOGGED=()
+check_one(){ # unit ep
+  local unit="$1" ep="$2" now ok=0 verdict
+  if ! systemctl is-enabled "$unit" >/dev/null 2>&1; then   # not installed here: say so once
+    [ -n "${SKIP_LOGGED[$unit]:-}" ] && return 0
+    SKIP_LOGGED[$unit]=1; FAILS=0; LAST=""
+    event "$unit" "$ep" skipped "unit not installed/enabled; ${ep} is not watched"
+    return 0
+  fi
+  now="$(date +%s)"
+  load_state "$unit"
+  probe "$ep" && ok=1
+  if [ "$ok" -eq 1 ]; then
+    [ "$FAILS" -gt 0 ] && { FAILS=0; event "$unit" "$ep" recovered ""; }
+    if [ "$USAGE_RECORD" -eq 1 ]; then
+      "${SCRIPT_DIR}/store-usage.sh" record --from-ps --host "$ep" --source watchdog >/dev/null 2>&1 || true
+    fi
+    save_state "$unit"; return 0
+  fi
+  FAILS=$((FAILS+1))
+  RESTARTS="$(recent_restarts "$now" "$RESTARTS")"
+  verdict="$(decide "$now" 0 "$FAILS" "$RESTARTS")"
+  case "$verdict" in
+    wait)    event "$unit" "$ep" probe_failed "${FAILS}/${FAIL_THRESHOLD}" ;;
+    backoff) event "$unit" "$ep" backoff "next restart after $(backoff_secs "$(count_csv "$RESTARTS")")s since last" ;;
+    capped)  event "$unit" "$ep" capped "${MAX_RESTARTS_PER_HOUR} restarts in the last hour; not restarting" ;;
+    restart)
+      if [ "$DRY_RUN" -eq 1 ]; then
+        event "$unit" "$ep" restart_dry_run ""
+      else
+        if systemctl restart "$unit"; then
+          RESTARTS="${RESTARTS:+$RESTARTS,}$now"; FAILS=0
+          event "$unit" "$ep" restarted ""
+        else
+          event "$unit" "$ep" restart_failed "systemctl restart returned non-zero"
+        fi
+      fi ;;
+  esac
+  save_state "$unit"
+}
+
+# ------------------------------------------------------------------------------
+# Main loop
+# ------------------------------------------------------------------------------
+echo "== ollama watchdog == instances: ${INSTANCES} interval=${INTERVAL}s threshold=${FAIL_THRESHOLD}"
+while :; do
+  for pair in $INSTANCES; do
+    check_one "${pair%%=*}" "${pair#*=}" || true
+  done
+  [ "$ONCE" -eq 1 ] && break
+  sleep "$INTERVAL"
+done
@@ -0,0 +1,70 @@
+#!/usr/bin/env bash
+# bake-sweep.test.sh — shared warm-up/measurement (ollama/bench-gen.sh) and a stubbed sweep
+# The daemon is stubbed: every request is logged; tok/s grows with num_gpu up to ng16,
+# ng24 fails to load.
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+O="$STACK_DIR/ollama"
+REQ="$T_TMP/requests.log"
+
+mkdir -p "$T_TMP/bin"
+cat > "$T_TMP/bin/curl" <<'STUB'
+#!/usr/bin/env bash
+url="${!#}" data=""
+while [ $# -gt 0 ]; do [ "$1" = "-d" ] && data="$2"; shift; done
+echo "${url##*/api/} $data" >> "$REQ"
+case "$url" in
+  */api/generate)
+    m="$(jq -r .model <<<"$data")"; ng="$(sed -nE 's/.*-ng([0-9]+).*/\1/p' <<<"$m")"
+    if [ "${ng:-0}" -ge 24 ]; then echo '{"error":"out of memory"}'
+    else jq -cn --argjson ng "${ng:-1}" '{done:true, eval_count:($ng * 10), eval_duration:1000000000}'; fi ;;
+  */api/ps)   echo '{"models":[]}' ;;
+  */api/tags) echo '{"models":[]}' ;;
+  *) echo '{}' ;;
+esac
+STUB
+cat > "$T_TMP/bin/ollama" <<'STUB'
+#!/usr/bin/env bash
+echo "create $2" >> "$REQ"
+STUB
+chmod +x "$T_TMP/bin/curl" "$T_TMP/bin/ollama"
+export REQ
+
+# bench-gen.sh
+: > "$REQ"
+out="$(PATH="$T_TMP/bin:$PATH" bash -c 'source "$1"; sleep(){ :; }; echo "$(calc_tokps 128 2000000000)"; warm_up h:1 m-ng8
+  echo "$(gen_tokps h:1 m-ng8 hi "{\"num_predict\":8}")"; gen_tokps h:1 m-ng32 hi "{}"' _ "$O/bench-gen.sh")"
+assert_eq $'64.00\n80.00\n0.00' "$out" "calc_tokps, gen_tokps, and 0.00 on a daemon error"
+assert_eq 64 "$(head -n1 "$REQ" | cut -d' ' -f2- | jq '.options.num_predict')" "warm-up passes num_predict as an option"
+
+# Sweep: every measured request follows a warm-up of the same variant
+: > "$REQ"; S="$T_TMP/store"; mkdir -p "$S"
+sweep(){
+  PATH="$T_TMP/bin:$PATH" LOG_DIR="$T_TMP/logs" RUN_TS=t OLLAMA_MODELS_DIR="$S" OLLAMA_BIN="$T_TMP/bin/ollama" \
+    bash "$O/bake-sweep.sh" --base gemma3:4b --gpu nvidia-3090ti --num-gpu "8 16 24 32" --no-gpu-check --host h:1 "$@" 2>&1
+}
+out="$(sweep)"; rc=$?
+assert_eq 0 "$rc" "sweep with a winner exits 0"
+gens="$(grep '^generate ' "$REQ" | cut -d' ' -f2- | jq -r '"\(.prompt == "warm up") \(.model)"')"
+assert_eq "true LLM-FuZe-gemma3-4b-nvidia-3090ti-ng8:latest
+false LLM-FuZe-gemma3-4b-nvidia-3090ti-ng8:latest
+true LLM-FuZe-gemma3-4b-nvidia-3090ti-ng16:latest
+false LLM-FuZe-gemma3-4b-nvidia-3090ti-ng16:latest
+true LLM-FuZe-gemma3-4b-nvidia-3090ti-ng24:latest
+false LLM-FuZe-gemma3-4b-nvidia-3090ti-ng24:latest" "$gens" "warm-up then measure, stopping at the first load failure"
+assert_eq "LLM-FuZe-gemma3-4b-nvidia-3090ti-ng16 160" \
+  "$(jq -r '"\(.variant) \(.tokens_per_sec)"' "$T_TMP/logs/ollama_sweep_t.best.json")" "best.json records the winner"
+assert_eq "skipped" "$(awk -F',' '$4==32{print $8}' "$T_TMP/logs/ollama_sweep_t.csv")" "higher num_gpu is skipped"
+
+# --force-break-lock reaches the bake-modelfile.sh children
+bash -c 'source "$1"; store_lock "$2" exclusive; touch "$3"; sleep 10' _ "$O/store-lock.sh" "$S" "$T_TMP/held" >/dev/null 2>&1 &
+holder=$!
+while [ ! -e "$T_TMP/held" ]; do sleep 0.05; done
+rc=0; out="$(STORE_LOCK_TIMEOUT=1 sweep --num-gpu 8)" || rc=$?
+assert_eq 1 "$rc" "a held store lock fails the bake (no winner)"
+assert_contains "$out" "could not take the shared store lock" "the bake waited for the lock and gave up"
+rc=0; out="$(STORE_LOCK_TIMEOUT=1 sweep --num-gpu 8 --force-break-lock)" || rc=$?
+assert_eq 0 "$rc" "--force-break-lock bakes despite the held lock"
+kill "$holder" 2>/dev/null; wait "$holder" 2>/dev/null
+
+finish
@@ -0,0 +1,55 @@
+#!/usr/bin/env bash
+# cleanup-variants.test.sh — pattern, keep, --unused-days and --stale selection on a fixture store
+# The daemon is stubbed: /api/tags lists the store's manifests, `ollama rm` deletes one.
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+O="$STACK_DIR/ollama"
+S="$T_TMP/store"; LIB="$S/manifests/registry.ollama.ai/library"
+P="LLM-FuZe-"; G="${P}gemma3-4b-i-f16-nvidia-3090ti"; L="${P}llama3.1-8b-nvidia-5090-ng33"
+
+mkdir -p "$T_TMP/bin"
+cat > "$T_TMP/bin/curl" <<'STUB'
+#!/usr/bin/env bash
+source "$STACK_DIR/common/store.sh"
+find "$FX_STORE/manifests" -type f | sort | while IFS= read -r m; do manifest_name "${m#"$FX_STORE"/}"; done \
+  | jq -R '{name:.}' | jq -s '{models:.}'
+STUB
+cat > "$T_TMP/bin/ollama" <<'STUB'
+#!/usr/bin/env bash
+source "$STACK_DIR/common/store.sh"
+[ "$1" = rm ] && [ -f "$FX_STORE/$(manifest_rel "$2")" ] && rm -f "$FX_STORE/$(manifest_rel "$2")"
+STUB
+chmod +x "$T_TMP/bin/curl" "$T_TMP/bin/ollama"
+export STACK_DIR FX_STORE="$S"
+
+fixture(){ bash "$O/store-fixtures.sh" --out "$S" --force >/dev/null; }
+left(){ find "$LIB" -type f | sed "s#^$LIB/##" | LC_ALL=C sort | paste -sd' ' -; }
+clean(){ PATH="$T_TMP/bin:$PATH" bash "$O/cleanup-variants.sh" --store "$S" --ollama-bin "$T_TMP/bin/ollama" "$@" 2>&1; }
+all="$G-ng16/latest $G-ng24/latest $G-ng32/latest $L/latest gemma3/4b-it-fp16 llama3.1/8b"
+
+fixture
+out="$(clean)"
+assert_contains "$out" "Candidates to remove (4)" "dry-run lists the four baked variants"
+assert_eq "$all" "$(left)" "dry-run deletes nothing"
+
+out="$(clean --keep 'ng16' --force --yes)"
+assert_contains "$out" "Removed on 127.0.0.1:11434: 3/3" "--force removes matches"
+assert_eq "$G-ng16/latest gemma3/4b-it-fp16 llama3.1/8b" "$(left)" "bases and --keep matches survive"
+
+# --unused-days: old usage is removed; fresh manifests (no usage) are kept
+fixture
+now="$(date +%s)"
+{ jq -cn --arg n "$G-ng24:latest" --argjson t $((now - 90*86400)) '{ts:$t, name:$n, count:1, source:"test"}'
+  jq -cn --arg n "$G-ng32:latest" --argjson t "$now" '{ts:$t, name:$n, count:1, source:"test"}'; } > "$S/.fuze-usage.jsonl"
+out="$(clean --unused-days 30 --force --yes)"
+assert_contains "$out" "removed: $G-ng24:latest" "unused for 90 days is removed"
+assert_eq "$G-ng16/latest $G-ng32/latest $L/latest gemma3/4b-it-fp16 llama3.1/8b" "$(left)" "recently used and fresh variants are kept"
+
+# --stale: only recorded-stale variants (bake record digest differs from the base now)
+fixture
+jq -cn --arg v "$L" '{ts:1, variant:$v, base:"llama3.1:8b", base_digest:("sha256:" + ("0" * 64))}' > "$S/.fuze-bakes.jsonl"
+out="$(clean --stale --force --yes)"
+assert_contains "$out" "Candidates to remove (1)" "--stale narrows to the recorded-stale variant"
+assert_eq "$G-ng16/latest $G-ng24/latest $G-ng32/latest gemma3/4b-it-fp16 llama3.1/8b" "$(left)" "only the stale variant is removed"
+
+finish
@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+# tests/lib.sh — tiny assert helpers for the stack's shell tests (source this file)
+# Each *.test.sh sources this, calls the asserts, and ends with `finish`.
+
+STACK_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+TESTS_RUN=0
+TESTS_FAILED=0
+T_TMP="$(mktemp -d)"
+trap 'rm -rf "$T_TMP"' EXIT
+
+pass(){ TESTS_RUN=$((TESTS_RUN+1)); echo "  ok   - $1"; }
+fail(){ TESTS_RUN=$((TESTS_RUN+1)); TESTS_FAILED=$((TESTS_FAILED+1)); echo "  FAIL - $1"; [ -n "${2:-}" ] && echo "$2" | sed 's/^/         /'; return 0; }
+
+assert_eq(){ # expected actual name
+  if [ "$1" = "$2" ]; then pass "$3"; else fail "$3" "expected: $1"$'\n'"actual:   $2"; fi
+}
+assert_contains(){ # haystack needle name
+  if grep -qF -- "$2" <<<"$1"; then pass "$3"; else fail "$3" "missing: $2"$'\n'"in: $1"; fi
+}
+assert_not_contains(){ # haystack needle name
+  if grep -qF -- "$2" <<<"$1"; then fail "$3" "unexpected: $2"; else pass "$3"; fi
+}
+assert_rc(){ # expected_rc name cmd...
+  local want="$1" name="$2" rc=0; shift 2
+  "$@" >/dev/null 2>&1 || rc=$?
+  assert_eq "$want" "$rc" "$name"
+}
+
+finish(){
+  echo "  ${TESTS_RUN} run, ${TESTS_FAILED} failed"
+  [ "$TESTS_FAILED" -eq 0 ]
+}
@@ -0,0 +1,64 @@
+#!/usr/bin/env bash
+# naming.test.sh — golden cases for common/naming.sh (GPUs and bases in use)
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+source "$STACK_DIR/common/naming.sh"
+
+while IFS='|' read -r raw want; do
+  assert_eq "$want" "$(normalize_gpu_label "$raw")" "gpu label: $raw"
+done <<'CASES'
+NVIDIA GeForce RTX 5090|nvidia-5090
+NVIDIA GeForce RTX 3090 Ti|nvidia-3090ti
+NVIDIA GeForce RTX 4090|nvidia-4090
+NVIDIA GeForce RTX 4070 SUPER|nvidia-4070super
+nvidia-5090|nvidia-5090
+nvidia-3090ti|nvidia-3090ti
+5090|nvidia-5090
+CASES
+
+while IFS='|' read -r base want; do
+  assert_eq "$want" "$(base_alias "$base")" "base alias: $base"
+done <<'CASES'
+gemma3:4b-it-fp16|gemma3-4b-i-f16
+gemma3:27b-it-fp16|gemma3-27b-i-f16
+gemma3:27b-it|gemma3-27b
+gemma3:4b|gemma3-4b
+gemma3:latest|gemma3-latest
+llama4:16x17b|llama4-16x17b
+llama4:128x17b|llama4-128x17b
+llama4:17b-scout-16e-instruct-fp16|llama4-17b-scout-16e-instruct-f16
+llama3.1:8b-text-fp16|llama3.1-8b-text-f16
+deepseek-r1:70b|deepseek-r1-70b
+deepseek-r1:671b-q4_K_M|deepseek-r1-671b-q4_K_M
+gpt-oss:20b|gpt-oss-20b
+qwen3:30b-a3b-bf16|qwen3-30b-a3b-b16
+kronos483/MedEmbed-large-v0.1:latest|kronos483-MedEmbed-large-v0.1-latest
+CASES
+
+# Quant tag sits between the base alias and the GPU, so cleanup's -nvidia-<gpu>-ng<N> tail still matches
+assert_eq "LLM-FuZe-gemma3-4b-q4_K_M-nvidia-5090-ng20" "$(ALIAS_PREFIX=LLM-FuZe- variant_name gemma3:4b nvidia-5090 20 q4_K_M)" "variant name carries the quant tag"
+assert_eq "LLM-FuZe-gemma3-4b-nvidia-5090-ng20" "$(ALIAS_PREFIX=LLM-FuZe- variant_name gemma3:4b nvidia-5090 20)" "no quant tag without --quantize"
+
+# bake-modelfile.sh: name and rendered Modelfile per GPU/base (golden: tests/fixtures/modelfiles/)
+GOLDEN="$STACK_DIR/tests/fixtures/modelfiles"
+while IFS='|' read -r base gpu ng ctx quant; do
+  out="$(bash "$STACK_DIR/ollama/bake-modelfile.sh" --base "$base" --gpu "$gpu" --num-gpu "$ng" --num-ctx "$ctx" \
+          ${quant:+--quantize "$quant"} --no-gpu-check --out "$T_TMP/modelfiles" 2>&1)"
+  v="$(sed -nE 's/^Variant   : (.*)$/\1/p' <<<"$out")"
+  if [ -f "$GOLDEN/$v.Modelfile" ]; then
+    assert_eq "$(cat "$GOLDEN/$v.Modelfile")" "$(cat "$T_TMP/modelfiles/$v.Modelfile" 2>/dev/null)" "Modelfile: $base on $gpu${quant:+ ($quant)}"
+  else
+    fail "Modelfile: $base on $gpu${quant:+ ($quant)}: no golden for '${v}'"
+  fi
+done <<'CASES'
+gemma3:27b-it|NVIDIA GeForce RTX 3090 Ti|28|4096|
+gemma3:4b-it-fp16|nvidia-3090ti|16|8192|
+llama3.1:8b-text-fp16|NVIDIA GeForce RTX 5090|33|4096|
+qwen3:30b-a3b-bf16|NVIDIA GeForce RTX 4090|40|4096|
+gpt-oss:20b|NVIDIA GeForce RTX 4070 SUPER|24|4096|
+gemma3:4b|5090|20|4096|q4_K_M
+kronos483/MedEmbed-large-v0.1:latest|4090|12|2048|
+CASES
+assert_eq "$(ls "$GOLDEN" | wc -l)" "$(ls "$T_TMP/modelfiles"/*.Modelfile | wc -l)" "every golden Modelfile is rendered"
+
+finish
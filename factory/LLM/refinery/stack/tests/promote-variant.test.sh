@@ -0,0 +1,37 @@
+#!/usr/bin/env bash
+# promote-variant.test.sh — scores are validated before the daemon is touched
+# The daemon is stubbed: every curl call is logged, /api/tags lists the variant.
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+PROMOTE="$STACK_DIR/ollama/promote-variant.sh"
+V="LLM-FuZe-gemma3-4b-nvidia-5090-ng20"
+
+mkdir -p "$T_TMP/bin"
+cat > "$T_TMP/bin/curl" <<STUB
+#!/usr/bin/env bash
+echo "\$*" >> "$T_TMP/calls"
+case "\$*" in
+  */api/tags*)     echo '{"models":[{"name":"$V:latest"}]}' ;;
+  */api/generate*) echo '{"done":true,"response":"ok"}' ;;
+esac
+STUB
+chmod +x "$T_TMP/bin/curl"
+promote(){ PATH="$T_TMP/bin:$PATH" LOG_DIR="$T_TMP" OLLAMA_MODELS_DIR="$T_TMP/none" \
+  bash "$PROMOTE" promote --journal "$T_TMP/journal.jsonl" --host h:1 "$@" >"$T_TMP/out" 2>&1; }
+
+for bad in fast 12,5 "1e3" "-4"; do
+  : > "$T_TMP/calls"
+  assert_rc 2 "--score '$bad' is refused" promote --variant "$V" --score "$bad"
+  assert_eq "" "$(cat "$T_TMP/calls")" "--score '$bad': no API call made"
+done
+
+jq -n --arg v "$V" '{variant:$v, tokens_per_sec:"n/a"}' > "$T_TMP/best.json"
+: > "$T_TMP/calls"
+assert_rc 2 "non-numeric tokens_per_sec in the results JSON is refused" promote "$T_TMP/best.json"
+assert_eq "" "$(cat "$T_TMP/calls")" "results JSON: no API call made"
+assert_eq "" "$(cat "$T_TMP/journal.jsonl" 2>/dev/null)" "nothing journaled"
+
+assert_rc 0 "a numeric score promotes" promote --variant "$V" --score 42.5
+assert_eq "42.5" "$(jq -r '.score' "$T_TMP/journal.jsonl")" "score journaled as a number"
+
+finish
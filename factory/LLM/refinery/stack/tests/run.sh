@@ -0,0 +1,13 @@
+#!/usr/bin/env bash
+# tests/run.sh — run the stack's shell tests (all, or those matching $1)
+set -uo pipefail
+
+DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+failed=0
+for t in "$DIR"/*.test.sh; do
+  [ -f "$t" ] || continue
+  [ -n "${1:-}" ] && [[ "$(basename "$t")" != *"$1"* ]] && continue
+  echo "== $(basename "$t" .test.sh)"
+  bash "$t" || failed=$((failed+1))
+done
+[ "$failed" -eq 0 ] && echo "All tests passed." || { echo "$failed test file(s) failed."; exit 1; }
@@ -0,0 +1,42 @@
+#!/usr/bin/env bash
+# store-cleanup.test.sh — --verify halts before deleting any source file on a mismatch
+# Store: store-fixtures.sh with hardlinked duplicates in DIR.alt (same-FS dedupe path).
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+O="$STACK_DIR/ollama"
+S="$T_TMP/store"; A="$S.alt"
+
+bash "$O/store-fixtures.sh" --print-spec | jq '.corrupt.hardlinked_dupes = 3' > "$T_TMP/spec.json"
+fixture(){ bash "$O/store-fixtures.sh" --out "$S" --spec "$T_TMP/spec.json" --force >/dev/null; }
+alt_files(){ find "$A" -type f ! -path '*/.fuze-store.lock*' 2>/dev/null | sort | xargs -r sha256sum; }
+cleanup(){ # mode -> rc
+  bash "$O/store-cleanup.sh" --canon "$S" --alt "$A" --no-stop --verify "$1" --verify-jobs 2 \
+    --verify-report "$T_TMP/report.tsv" > "$T_TMP/out" 2>&1
+}
+corrupt(){ # same|size -> one ALT blob unlinked from the store and changed
+  local f; f="$(find "$A/blobs" -type f | sort | head -n1)"
+  cp --remove-destination "$f" "$f.tmp" && mv -f "$f.tmp" "$f"
+  if [ "$1" = "same" ]; then printf 'X' | dd of="$f" bs=1 seek=0 conv=notrunc status=none
+  else printf 'X' >> "$f"; fi
+}
+
+for mode in full fast; do
+  fixture
+  [ "$mode" = "full" ] && corrupt same || corrupt size
+  before="$(alt_files)"
+  rc=0; cleanup "$mode" || rc=$?
+  assert_eq 3 "$rc" "$mode: a corrupted ALT copy halts with exit 3"
+  assert_contains "$(cat "$T_TMP/out")" "nothing deleted from $A" "$mode: halt is reported"
+  assert_eq "$before" "$(alt_files)" "$mode: no source file deleted or changed"
+done
+
+for mode in full fast; do
+  fixture
+  rc=0; cleanup "$mode" || rc=$?
+  assert_eq 0 "$rc" "$mode: clean duplicates verify"
+  assert_contains "$(cat "$T_TMP/out")" "deleted 3 verified source files" "$mode: verified sources are deleted"
+  assert_eq "" "$(ls -d "$A" 2>/dev/null)" "$mode: emptied ALT is retired with its lock files"
+  assert_rc 0 "$mode: canonical store still audits clean" bash "$O/store-verify.sh" --dst "$S" --mode full --report "$T_TMP/audit.tsv"
+done
+
+finish
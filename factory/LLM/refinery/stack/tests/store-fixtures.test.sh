@@ -0,0 +1,52 @@
+#!/usr/bin/env bash
+# store-fixtures.test.sh — generate a corrupted fixture store, --check it, and run
+# variants-stale / store-verify / store-quota against it with the expected findings
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+O="$STACK_DIR/ollama"
+S="$T_TMP/store"; FX="$S/fixture.json"
+P="LLM-FuZe-"; G="${P}gemma3-4b-i-f16-nvidia-3090ti"
+
+bash "$O/store-fixtures.sh" --print-spec \
+  | jq '.corrupt = {missing_blobs:1, orphan_blobs:2, malformed_names:1, double_prefix:1, hardlinked_dupes:2}' > "$T_TMP/spec.json"
+
+# generate -> --check
+out="$(bash "$O/store-fixtures.sh" --out "$S" --spec "$T_TMP/spec.json")"
+assert_contains "$out" "manifests: 8 (4 variants), orphan blobs: 2, missing blobs: 1, hardlinks: 2" "generate reports the requested corruptions"
+assert_rc 0 "--check passes on the generated tree" bash "$O/store-fixtures.sh" --out "$S" --check
+assert_rc 1 "regenerating without --force is refused" bash "$O/store-fixtures.sh" --out "$S" --spec "$T_TMP/spec.json"
+
+# variants-stale: only well-formed variants are classified; no bake records -> inferred only
+rep="$(bash "$O/variants-stale.sh" --store "$S" --json 2>/dev/null)"
+assert_eq 5 "$(jq 'length' <<<"$rep")" "malformed name is not classified as a variant"
+assert_eq "gemma3:4b-it-fp16" "$(jq -r --arg n "${P}${P}gemma3-4b-i-f16-nvidia-dup1-ng1:latest" '.[] | select(.name==$n) | .base' <<<"$rep")" \
+  "double-prefixed variant resolves to its real base"
+assert_eq "stale inferred" "$(jq -r --arg n "${P}llama3.1-8b-nvidia-5090-ng33:latest" '.[] | select(.name==$n) | "\(.status) \(.how)"' <<<"$rep")" \
+  "quantized variant is inferred stale"
+assert_eq 0 "$(bash "$O/variants-stale.sh" --store "$S" --count)" "nothing recorded stale without bake records"
+
+# store-verify: audit finds exactly the missing blob; the hardlinked .alt copies verify
+missing="$(jq -r '.models[] | select(.missing_blob) | .missing_blob | sub(":"; "-")' "$FX")"
+rc=0; bash "$O/store-verify.sh" --dst "$S" --mode full --report "$T_TMP/audit.tsv" >/dev/null 2>&1 || rc=$?
+assert_eq 1 "$rc" "full audit fails on the corrupted store"
+assert_eq "MISSING_BLOB blobs/$missing" "$(awk -F'\t' 'NR>1 && $1!="OK" {print $1, $2}' "$T_TMP/audit.tsv")" "the only discrepancy is the removed blob"
+assert_rc 0 "fast verify of the hardlinked duplicates passes" \
+  bash "$O/store-verify.sh" --src "$S.alt" --dst "$S" --mode fast --report "$T_TMP/alt.tsv"
+
+# store-quota: every matching variant (double-prefixed included) is a candidate; bases and malformed never
+out="$(LOG_DIR="$T_TMP" bash "$O/store-quota.sh" --store "$S" --limit 1M --journal "$T_TMP/j.jsonl" 2>/dev/null)"; rc=$?
+assert_eq 1 "$rc" "quota can't be met by evicting variants alone"
+assert_eq 5 "$(awk '/ last used / {n++} END {print n+0}' <<<"$out")" "five eviction candidates"
+assert_not_contains "$out" "malformed1" "malformed name is not a candidate"
+assert_not_contains "$out" "gemma3:4b-it-fp16 " "bases are not candidates"
+
+# --check notices tampering; --force keeps a foreign DIR.alt
+printf 'x' >> "$S/blobs/$(jq -r '.orphan_blobs[0] | sub(":"; "-")' "$FX")"
+assert_rc 1 "--check fails after a blob is modified" bash "$O/store-fixtures.sh" --out "$S" --check
+touch "$S.alt/not-ours"
+assert_rc 1 "--force refuses to remove a DIR.alt with foreign files" bash "$O/store-fixtures.sh" --out "$S" --spec "$T_TMP/spec.json" --force
+assert_eq "yes" "$([ -e "$S.alt/not-ours" ] && [ -d "$S/blobs" ] && echo yes)" "nothing was removed"
+rm -f "$S.alt/not-ours"
+assert_rc 0 "--force replaces DIR and its generated DIR.alt" bash "$O/store-fixtures.sh" --out "$S" --spec "$T_TMP/spec.json" --force
+
+finish
@@ -0,0 +1,64 @@
+#!/usr/bin/env bash
+# store-lock.test.sh — two processes contending for ollama/store-lock.sh on a temp store
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+LOCK_LIB="$STACK_DIR/ollama/store-lock.sh"
+
+STORE="$T_TMP/store"; mkdir -p "$STORE"
+
+# holder MODE TAG: take the lock in a background process, signal ready, hold until released
+holder(){
+  bash -c 'source "$1"; store_lock "$2" "$3"; touch "$4.ready"; while [ ! -e "$4.go" ]; do sleep 0.05; done' \
+    _ "$LOCK_LIB" "$STORE" "$1" "$T_TMP/$2" >/dev/null &
+  local pid=$!
+  while [ ! -e "$T_TMP/$2.ready" ]; do sleep 0.05; done
+  echo "$pid"
+}
+release(){ touch "$T_TMP/$1.go"; while kill -0 "$2" 2>/dev/null; do sleep 0.05; done; }
+try_lock(){ # mode -> rc, stderr in $T_TMP/err
+  STORE_LOCK_TIMEOUT=1 bash -c 'source "$1"; store_lock "$2" "$3"' _ "$LOCK_LIB" "$STORE" "$1" 2>"$T_TMP/err"
+}
+
+# exclusive holder blocks a shared taker; contention message names the holder
+a="$(holder exclusive a)"
+rc=0; try_lock shared || rc=$?
+assert_eq 4 "$rc" "shared waits for exclusive and fails with 4 after the timeout"
+assert_contains "$(cat "$T_TMP/err")" "pid=$a" "contention message names the exclusive holder"
+assert_contains "$(cat "$STORE/.fuze-store.lock.d/"*)" "mode=exclusive" "exclusive holder is recorded"
+release a "$a"
+assert_eq "" "$(ls "$STORE/.fuze-store.lock.d")" "holder record removed when the holder exits"
+
+# shared holders coexist and both record themselves; exclusive then waits for both
+a="$(holder shared a2)"; b="$(holder shared b2)"
+assert_eq 2 "$(ls "$STORE/.fuze-store.lock.d" | wc -l | tr -d ' ')" "every shared holder is recorded"
+rc=0; try_lock exclusive || rc=$?
+assert_eq 4 "$rc" "exclusive waits for shared holders"
+assert_contains "$(cat "$T_TMP/err")" "pid=$b" "contention message lists shared holders"
+rc=0; STORE_LOCK_TIMEOUT=1 bash -c 'source "$1"; store_lock_run "$2" exclusive true' _ "$LOCK_LIB" "$STORE" 2>/dev/null || rc=$?
+assert_eq 4 "$rc" "store_lock_run exclusive returns 4 on timeout"
+release a2 "$a"; release b2 "$b"
+
+# stale: lock held but the only recorded holder is dead (killed; its child inherited the descriptor)
+{ bash -c 'source "$1"; store_lock "$2" exclusive; sleep 30 >/dev/null 2>&1 & echo $! > "$3"; kill -9 $$' \
+    _ "$LOCK_LIB" "$STORE" "$T_TMP/orphan"; } 2>/dev/null
+assert_contains "$(ls "$STORE/.fuze-store.lock.d")" "." "killed holder leaves its record behind"
+rc=0; try_lock exclusive || rc=$?
+assert_eq 4 "$rc" "lock kept by an inherited descriptor still blocks"
+assert_contains "$(cat "$T_TMP/err")" "no recorded holder is alive" "dead-only holders reported as stale"
+assert_eq "" "$(ls "$STORE/.fuze-store.lock.d")" "dead holder records are pruned"
+kill "$(cat "$T_TMP/orphan")" 2>/dev/null; sleep 0.1
+
+a="$(holder exclusive a3)"
+out="$(bash -c 'source "$1"; store_lock_stale "$2" && echo stale || echo live' _ "$LOCK_LIB" "$STORE")"
+assert_eq live "$out" "a live exclusive holder is not stale"
+release a3 "$a"
+
+# re-entrant per store, shared -> exclusive upgrade, stderr left alone, release + remove
+out="$(bash -c 'source "$1"; store_lock "$2" shared; store_lock "$2" exclusive; store_lock "$2" exclusive
+                echo to-stderr >&2; store_lock_release "$2" remove; ls -A "$2"' _ "$LOCK_LIB" "$STORE" 2>&1)"
+assert_eq "to-stderr" "$out" "re-entrant, keeps stderr, remove leaves no lock files"
+rc=0; bash -c 'source "$1"; STORE_LOCK_TIMEOUT=1; store_lock "$2" exclusive; store_lock "$2" exclusive; store_lock_run "$2" shared true' \
+  _ "$LOCK_LIB" "$STORE" 2>/dev/null || rc=$?
+assert_eq 0 "$rc" "second store_lock and store_lock_run in the holder do not deadlock"
+
+finish
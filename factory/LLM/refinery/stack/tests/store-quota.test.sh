@@ -0,0 +1,70 @@
+#!/usr/bin/env bash
+# store-quota.test.sh — deterministic eviction selection on a store-fixtures.sh store
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+QUOTA="$STACK_DIR/ollama/store-quota.sh"
+
+S="$T_TMP/store"
+bash "$STACK_DIR/ollama/store-fixtures.sh" --out "$S" >/dev/null || { fail "fixture store"; finish; exit 1; }
+total="$(find "$S/blobs" -type f -printf '%s\n' | awk '{s+=$1} END{print s}')"
+V="LLM-FuZe-gemma3-4b-i-f16-nvidia-3090ti"
+L="LLM-FuZe-llama3.1-8b-nvidia-5090-ng33:latest"
+
+usage_at(){ # "name epoch" lines -> usage db
+  : > "$S/.fuze-usage.jsonl"
+  while read -r n t; do jq -cn --arg n "$n" --argjson t "$t" '{ts:$t, name:$n, count:1, source:"test"}' >> "$S/.fuze-usage.jsonl"; done
+}
+evicted(){ # limit [args] -> proposed names, in order
+  local lim="$1"; shift
+  LOG_DIR="$T_TMP" bash "$QUOTA" --store "$S" --journal "$T_TMP/journal.jsonl" --limit "$lim" "$@" 2>/dev/null \
+    | awk '/ last used / {print $1}' | paste -sd' ' -
+}
+
+# Ties on last use are broken by name
+usage_at <<USAGE
+${V}-ng16:latest 1000
+${V}-ng24:latest 1000
+${V}-ng32:latest 1000
+$L 1000
+USAGE
+assert_eq "${V}-ng16:latest" "$(evicted $((total - 1)))" "tie: 1 byte over evicts the first name"
+assert_eq "${V}-ng16:latest ${V}-ng24:latest ${V}-ng32:latest $L" "$(evicted 1)" "tie: full order is by name"
+assert_eq "$(evicted 1)" "$(evicted 1)" "selection is stable across runs"
+
+# Least recently used first; base models never match
+usage_at <<USAGE
+${V}-ng16:latest 3000
+${V}-ng24:latest 1000
+${V}-ng32:latest 2000
+$L 500
+USAGE
+assert_eq "$L ${V}-ng24:latest ${V}-ng32:latest ${V}-ng16:latest" "$(evicted 1)" "LRU order, bases excluded"
+
+# Pins: pin file (tag optional) and the current promoted alias target
+printf '# keep\n%s\n' "${V}-ng24" > "$S/.fuze-pins"
+jq -cn --arg v "${V}-ng32" '{action:"promote", host:"h", alias:"a", variant:$v}' > "$T_TMP/journal.jsonl"
+assert_eq "$L ${V}-ng16:latest" "$(evicted 1)" "pinned and promoted targets are never proposed"
+jq -cn '{action:"rollback", host:"h", alias:"a"}' >> "$T_TMP/journal.jsonl"
+assert_eq "$L ${V}-ng32:latest ${V}-ng16:latest" "$(evicted 1)" "a rolled-back target is evictable again"
+rc=0; LOG_DIR="$T_TMP" bash "$QUOTA" --store "$S" --journal "$T_TMP/journal.jsonl" --limit 1 >/dev/null 2>&1 || rc=$?
+assert_eq 1 "$rc" "still over budget after every candidate exits 1"
+assert_rc 0 "within budget exits 0" bash "$QUOTA" --store "$S" --limit "$total"
+
+# --execute refuses a proposal that cannot reach the budget unless --allow-partial
+rc=0; out="$(LOG_DIR="$T_TMP" bash "$QUOTA" --store "$S" --journal "$T_TMP/journal.jsonl" --limit 1 --execute --host 127.0.0.1:9 2>&1)" || rc=$?
+assert_eq 1 "$rc" "unsatisfiable --execute exits 1"
+assert_contains "$out" "refusing to delete" "unsatisfiable --execute is refused"
+assert_not_contains "$out" "- failed :" "nothing is sent to the daemon"
+rc=0; out="$(LOG_DIR="$T_TMP" bash "$QUOTA" --store "$S" --journal "$T_TMP/journal.jsonl" --limit 1 --execute --allow-partial --host 127.0.0.1:9 2>&1)" || rc=$?
+assert_contains "$out" "- failed : $L" "--allow-partial attempts the deletions"
+
+# --execute takes the lock before planning
+bash -c 'source "$1"; store_lock "$2" exclusive; touch "$3"; sleep 5' _ "$STACK_DIR/ollama/store-lock.sh" "$S" "$T_TMP/held" >/dev/null &
+holder=$!
+while [ ! -e "$T_TMP/held" ]; do sleep 0.05; done
+rc=0; out="$(STORE_LOCK_TIMEOUT=1 LOG_DIR="$T_TMP" bash "$QUOTA" --store "$S" --limit 1 --execute --host 127.0.0.1:9 2>&1)" || rc=$?
+assert_eq 4 "$rc" "--execute waits for the store lock"
+assert_not_contains "$out" "Proposed evictions" "no plan is made without the lock"
+kill "$holder" 2>/dev/null; wait "$holder" 2>/dev/null
+
+finish
@@ -0,0 +1,34 @@
+#!/usr/bin/env bash
+# store-usage.test.sh — compaction keeps last use and report totals while bounding the file
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+USAGE="$STACK_DIR/ollama/store-usage.sh"
+S="$T_TMP/store"; DB="$S/.fuze-usage.jsonl"
+bash "$STACK_DIR/ollama/store-fixtures.sh" --out "$S" >/dev/null || { fail "fixture store"; finish; exit 1; }
+V="LLM-FuZe-gemma3-4b-i-f16-nvidia-3090ti-ng16:latest"; W="LLM-FuZe-llama3.1-8b-nvidia-5090-ng33:latest"
+
+# Three days of watchdog ps samples every 30s plus some served requests
+now="$(date +%s)"; day=$(( now / 86400 * 86400 ))
+jq -nc --argjson d "$day" --arg v "$V" --arg w "$W" '
+  (range($d - 3*86400; $d - 1; 30) | {ts:., name:$v, count:0, source:"ps"}),
+  (range($d - 2*86400; $d - 1; 3600) | {ts:., name:$w, count:2, source:"wrapper"}),
+  {ts:($d - 86400 + 5), name:$w, count:1, source:"wrapper"}' > "$DB"
+last_before="$(bash "$USAGE" last-used --store "$S")"
+rep_before="$(bash "$USAGE" report --store "$S" --days 7 --json | jq -c '.models')"
+
+out="$(bash "$USAGE" compact --store "$S")"
+assert_contains "$out" "-> 5 rows" "one row per name and day"
+assert_eq "$last_before" "$(bash "$USAGE" last-used --store "$S")" "last use per name unchanged"
+assert_eq "$rep_before" "$(bash "$USAGE" report --store "$S" --days 7 --json | jq -c '.models')" "report requests and samples unchanged"
+bash "$USAGE" compact --store "$S" >/dev/null
+assert_eq 5 "$(wc -l < "$DB" | tr -d ' ')" "compacting again is a no-op"
+
+# Past USAGE_COMPACT_BYTES a record compacts; today's events stay raw
+jq -nc --argjson d "$day" --arg v "$V" 'range($d - 86400; $d - 1; 30) | {ts:., name:$v, count:0, source:"ps"}' >> "$DB"
+USAGE_COMPACT_BYTES=4096 bash "$USAGE" record --store "$S" --name "$V"
+assert_eq 6 "$(wc -l < "$DB" | tr -d ' ')" "record past the threshold compacts older days"
+assert_eq "manual $V" "$(tail -n1 "$DB" | jq -r '"\(.source) \(.name)"')" "today's events are kept raw"
+assert_eq 1 "$(bash "$USAGE" last-used --store "$S" | awk -F'\t' -v n="$V" -v t="$now" '$1==n{print ($2>=t)}')" \
+  "last use reflects the new event"
+
+finish
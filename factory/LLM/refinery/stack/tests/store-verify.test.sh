@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+# store-verify.test.sh — fast/full verification of migrated and deduplicated files
+# Stores: a store-fixtures.sh store (destination) and a copy of it (source).
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+VERIFY="$STACK_DIR/ollama/store-verify.sh"
+
+bash "$STACK_DIR/ollama/store-fixtures.sh" --out "$T_TMP/canon" >/dev/null || { fail "fixture store"; finish; exit 1; }
+mkdir -p "$T_TMP/alt"; cp -a "$T_TMP/canon/blobs" "$T_TMP/canon/manifests" "$T_TMP/alt/"
+blob="$(cd "$T_TMP/alt" && find blobs -type f | sort | head -n1)"
+man="$(cd "$T_TMP/alt" && find manifests -type f | sort | head -n1)"
+run_verify(){ # mode -> rc; report in $T_TMP/report.tsv
+  bash "$VERIFY" --src "$T_TMP/alt" --dst "$T_TMP/canon" --mode "$1" --jobs 2 --report "$T_TMP/report.tsv" >/dev/null 2>&1
+}
+
+# Same-FS duplicates are never copied: identical content, different mtime
+find "$T_TMP/alt" -type f -exec touch -d '2020-01-01' {} +
+assert_rc 0 "fast: identical duplicates with a different mtime pass" run_verify fast
+assert_rc 0 "full: identical duplicates pass" run_verify full
+
+rm -f "$T_TMP/alt/$blob"; ln "$T_TMP/canon/$blob" "$T_TMP/alt/$blob"
+assert_rc 0 "fast: hardlinked duplicate passes" run_verify fast
+
+rm -f "$T_TMP/alt/$blob"; cp "$T_TMP/canon/$blob" "$T_TMP/alt/$blob"; printf 'X' >> "$T_TMP/alt/$blob"
+assert_rc 1 "fast: size difference halts" run_verify fast
+assert_eq $'MISMATCH\t0' "$(awk -F'\t' -v p="$blob" '$2==p{print $1"\t"$4}' "$T_TMP/report.tsv")" \
+  "fast: MISMATCH row reports 0 bytes read"
+
+cp "$T_TMP/canon/$blob" "$T_TMP/alt/$blob"; printf '{}' > "$T_TMP/alt/$man"
+assert_rc 1 "fast: differing manifest bytes halt" run_verify fast
+
+finish
@@ -0,0 +1,24 @@
+#!/usr/bin/env bash
+# store.test.sh — common/store.sh name <-> manifest path round trips
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+source "$STACK_DIR/common/store.sh"
+
+while IFS='|' read -r name rel; do
+  assert_eq "$rel" "$(manifest_rel "$name")" "manifest_rel $name"
+  assert_eq "$(with_latest "$name")" "$(manifest_name "$rel")" "manifest_name $rel"
+done <<'CASES'
+gemma3:4b|manifests/registry.ollama.ai/library/gemma3/4b
+gemma3|manifests/registry.ollama.ai/library/gemma3/latest
+LLM-FuZe-gemma3-4b-nvidia-5090-ng20:latest|manifests/registry.ollama.ai/library/LLM-FuZe-gemma3-4b-nvidia-5090-ng20/latest
+ns/model:tag|manifests/registry.ollama.ai/ns/model/tag
+hf.co/ns/model:q4|manifests/hf.co/ns/model/q4
+CASES
+
+# LOG_DIR that cannot be created (a file in the way): per-user fallback
+: > "$T_TMP/not-a-dir"
+LOG_DIR="$T_TMP/not-a-dir/logs" XDG_STATE_HOME="$T_TMP/xdg"
+init_log_dir
+assert_eq "$T_TMP/xdg/fuze-stack/logs" "$LOG_DIR" "init_log_dir falls back when LOG_DIR is unusable"
+
+finish
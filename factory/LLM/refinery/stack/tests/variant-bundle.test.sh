@@ -0,0 +1,52 @@
+#!/usr/bin/env bash
+# variant-bundle.test.sh — export/import round trip and refusal paths on fixture stores
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+BUNDLE="$STACK_DIR/ollama/variant-bundle.sh"
+command -v zstd >/dev/null 2>&1 || { echo "  skip - zstd not installed"; finish; exit 0; }
+
+A="$T_TMP/a"; B="$T_TMP/b"; mkdir -p "$T_TMP/tmp"
+bash "$STACK_DIR/ollama/store-fixtures.sh" --out "$A" >/dev/null
+V="LLM-FuZe-llama3.1-8b-nvidia-5090-ng33"
+M="manifests/registry.ollama.ai/library/$V/latest"
+
+assert_rc 0 "export" env TMPDIR="$T_TMP/tmp" bash "$BUNDLE" export "$V" --store "$A" --out "$T_TMP/v.tar.zst"
+assert_rc 0 "import into an empty store" bash "$BUNDLE" import "$T_TMP/v.tar.zst" --store "$B"
+assert_eq "$(sha256sum < "$A/$M")" "$(sha256sum < "$B/$M")" "imported manifest is identical"
+assert_eq "" "$(ls -A "$B" | grep -F .import-staging)" "no staging left behind"
+
+# Same name, different manifest: refused, staging removed
+printf '{"layers":[]}' > "$B/$M"
+assert_rc 1 "different manifest with the same name is refused" bash "$BUNDLE" import "$T_TMP/v.tar.zst" --store "$B"
+assert_eq "" "$(ls -A "$B" | grep -F .import-staging)" "refusal removes the staging dir"
+
+# Unsafe paths in index.json: refused before anything is extracted or moved
+mkdir -p "$T_TMP/evil"
+zstd -dc "$T_TMP/v.tar.zst" | tar -C "$T_TMP/evil" -xf -
+jq '.manifest.path = "manifests/../../escaped"' "$T_TMP/evil/index.json" > "$T_TMP/evil/i" && mv "$T_TMP/evil/i" "$T_TMP/evil/index.json"
+tar -C "$T_TMP/evil" -cf - index.json manifests blobs | zstd -q -o "$T_TMP/evil.tar.zst"
+rc=0; out="$(bash "$BUNDLE" import "$T_TMP/evil.tar.zst" --store "$T_TMP/c" 2>&1)" || rc=$?
+assert_eq 1 "$rc" "manifest path with .. is refused"
+assert_contains "$out" "unsafe or malformed paths" "refusal names the reason"
+assert_eq "" "$(ls -A "$T_TMP/c" | grep -F .import-staging)" "unsafe bundle leaves no staging"
+jq --arg m "$M" '.manifest.path = $m | .blobs[0].path = "blobs/../../x"' "$T_TMP/evil/index.json" > "$T_TMP/evil/i" && mv "$T_TMP/evil/i" "$T_TMP/evil/index.json"
+tar -C "$T_TMP/evil" -cf - index.json manifests blobs | zstd -q -f -o "$T_TMP/evil.tar.zst"
+assert_rc 1 "blob path outside blobs/ is refused" bash "$BUNDLE" import "$T_TMP/evil.tar.zst" --store "$T_TMP/c"
+
+# Index without the referenced blobs: refused unless the store already has them
+jq --arg m "$M" '.manifest.path = $m | .blobs = []' "$T_TMP/evil/index.json" > "$T_TMP/evil/i" && mv "$T_TMP/evil/i" "$T_TMP/evil/index.json"
+tar -C "$T_TMP/evil" -cf - index.json manifests | zstd -q -f -o "$T_TMP/noblobs.tar.zst"
+rc=0; out="$(bash "$BUNDLE" import "$T_TMP/noblobs.tar.zst" --store "$T_TMP/d" 2>&1)" || rc=$?
+assert_eq 1 "$rc" "bundle without the referenced blobs is refused"
+assert_contains "$out" "blob(s) the manifest references" "refusal names the missing blobs"
+assert_eq "" "$(ls -A "$T_TMP/d" | grep -v '^\.fuze-store\.lock')" "refused import writes no manifest and leaves no staging"
+cp -a "$A/blobs" "$T_TMP/e"; mkdir -p "$T_TMP/f"; mv "$T_TMP/e" "$T_TMP/f/blobs"
+assert_rc 0 "blobs already in the store satisfy the manifest" bash "$BUNDLE" import "$T_TMP/noblobs.tar.zst" --store "$T_TMP/f"
+
+# Export failing mid-way (missing blob) leaves no temp dir or partial bundle
+rm -f "$A/blobs/$(jq -r '.layers[-1].digest | sub(":"; "-")' "$A/$M")"
+assert_rc 1 "export with a missing blob fails" env TMPDIR="$T_TMP/tmp" bash "$BUNDLE" export "$V" --store "$A" --out "$T_TMP/w.tar.zst"
+assert_eq "" "$(ls -A "$T_TMP/tmp")" "failed export removes its temp dir"
+assert_eq "" "$(ls "$T_TMP" | grep -F w.tar.zst)" "failed export leaves no partial bundle"
+
+finish
@@ -0,0 +1,59 @@
+#!/usr/bin/env bash
+# variants-stale.test.sh — recorded and shared-layer (inferred) classification on a fixture store
+# Store: tests/fixtures/shared-layers.json via store-fixtures.sh, then edited per case.
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+STALE="$STACK_DIR/ollama/variants-stale.sh"
+
+S="$T_TMP/store"; LIB="$S/manifests/registry.ollama.ai/library"
+bash "$STACK_DIR/ollama/store-fixtures.sh" --out "$S" --spec "$STACK_DIR/tests/fixtures/shared-layers.json" >/dev/null \
+  || { fail "fixture store"; finish; exit 1; }
+P="LLM-FuZe-"
+digest(){ echo "sha256:$(sha256sum "$LIB/$1" | awk '{print $1}')"; }
+
+# record_bake (bake-modelfile.sh and benchmark.sh bake_variant): digest from the daemon's /api/tags
+mkdir -p "$T_TMP/bin"
+cat > "$T_TMP/bin/curl" <<STUB
+#!/usr/bin/env bash
+jq -n --arg g "$(digest gemma3/4b-it-fp16)" --arg q "$(digest qwen3/8b)" \
+  '{models:[{name:"gemma3:4b-it-fp16", digest:(\$g|ltrimstr("sha256:"))}, {name:"qwen3:8b", digest:(\$q|ltrimstr("sha256:"))}]}'
+STUB
+chmod +x "$T_TMP/bin/curl"
+PATH="$T_TMP/bin:$PATH" bash -c 'source "$1"; record_bake "$2" h:1 "$3" gemma3:4b-it-fp16; record_bake "$2" h:1 "$4" qwen3:8b
+                                 record_bake "$2" h:1 "$5" llama3.1:8b' _ "$STACK_DIR/ollama/bake-record.sh" "$S" \
+  "${P}gemma3-4b-i-f16-nvidia-3090ti-ng16" "${P}qwen3-8b-nvidia-5090-ng20" "${P}llama3.1-8b-nvidia-5090-ng33" 2>"$T_TMP/err"
+assert_eq 2 "$(wc -l < "$S/.fuze-bakes.jsonl" | tr -d ' ')" "record_bake appends one record per known base"
+assert_contains "$(cat "$T_TMP/err")" "base digest for llama3.1:8b unknown" "record_bake warns when the base digest is unknown"
+
+# Recorded: llama ng33 baked from an older llama3.1:8b
+jq -cn --arg v "${P}llama3.1-8b-nvidia-5090-ng33" '{ts:1, variant:$v, base:"llama3.1:8b", base_digest:("sha256:" + ("0" * 64))}' \
+  >> "$S/.fuze-bakes.jsonl"
+# Recorded base gone: qwen3:8b removed after the bake
+rm -f "$LIB/qwen3/8b"
+# Shared weights with two bases: a mirror that sorts first must not win over the alias match
+mkdir -p "$LIB/aaa-mirror"; cp "$LIB/gemma3/4b-it-fp16" "$LIB/aaa-mirror/latest"
+# Our own malformed name sharing those weights is never taken as a base (sorts first)
+mkdir -p "$LIB/${P}gemma3-4b-i-f16-nvidia-bad-ng" "$LIB/${P}${P}gemma3-4b-i-f16-nvidia-dup-ng1"
+cp "$LIB/gemma3/4b-it-fp16" "$LIB/${P}gemma3-4b-i-f16-nvidia-bad-ng/latest"
+cp "$LIB/${P}gemma3-4b-i-f16-nvidia-3090ti-ng24/latest" "$LIB/${P}${P}gemma3-4b-i-f16-nvidia-dup-ng1/latest"
+# Quantized bake (bake-modelfile.sh --quantize): the quant tag is not part of the base alias
+mkdir -p "$LIB/${P}llama3.1-8b-q4_K_M-nvidia-5090-ng41"
+cp "$LIB/${P}llama3.1-8b-nvidia-5090-ng40/latest" "$LIB/${P}llama3.1-8b-q4_K_M-nvidia-5090-ng41/latest"
+# Unrecorded, no base sharing weights or named like it: phi3:mini removed
+rm -f "$LIB/phi3/mini"
+
+report="$(bash "$STALE" --store "$S" --json)"
+row(){ jq -r --arg n "$P$1:latest" '.[] | select(.name == $n) | [.status, .how, (.base // "-")] | join(" ")' <<<"$report"; }
+assert_eq "current recorded gemma3:4b-it-fp16" "$(row gemma3-4b-i-f16-nvidia-3090ti-ng16)" "recorded, base digest unchanged"
+assert_eq "stale recorded llama3.1:8b"         "$(row llama3.1-8b-nvidia-5090-ng33)"      "recorded, base digest changed"
+assert_eq "orphaned recorded qwen3:8b"         "$(row qwen3-8b-nvidia-5090-ng20)"         "recorded, base gone"
+assert_eq "current inferred gemma3:4b-it-fp16" "$(row gemma3-4b-i-f16-nvidia-3090ti-ng24)" "shared weights: the alias-matching base wins"
+assert_eq "current inferred aaa-mirror:latest" "$(row "${P}gemma3-4b-i-f16-nvidia-dup-ng1")" "shared weights, no alias match: malformed names are not bases"
+assert_eq "stale inferred llama3.1:8b"         "$(row llama3.1-8b-nvidia-5090-ng40)"      "quantized (no shared weights) with a named base"
+assert_eq "stale inferred llama3.1:8b"         "$(row llama3.1-8b-q4_K_M-nvidia-5090-ng41)" "quant-tagged name still finds its base"
+assert_eq "orphaned inferred -"                "$(row phi3-mini-nvidia-5090-ng12)"        "no base shares weights or matches the alias"
+
+assert_eq "${P}llama3.1-8b-nvidia-5090-ng33:latest" "$(bash "$STALE" --store "$S" --names)" "--names acts on recorded staleness only"
+assert_eq 3 "$(bash "$STALE" --store "$S" --count --include-inferred)" "--include-inferred adds inferred staleness"
+
+finish
@@ -0,0 +1,89 @@
+#!/usr/bin/env bash
+# watchdog.test.sh — ollama/watchdog-decide.sh restart policy and webhook filter on a simulated clock
+set -uo pipefail
+source "$(dirname "${BASH_SOURCE[0]}")/lib.sh"
+FAIL_THRESHOLD=3 BACKOFF_BASE=30 BACKOFF_MAX=200 MAX_RESTARTS_PER_HOUR=4
+source "$STACK_DIR/ollama/watchdog-decide.sh"
+
+# Backoff doubles per recent restart and stops at BACKOFF_MAX
+while IFS='|' read -r k want; do
+  assert_eq "$want" "$(backoff_secs "$k")" "backoff_secs $k"
+done <<'CASES'
+0|0
+1|30
+2|60
+3|120
+4|200
+9|200
+CASES
+
+assert_eq 0 "$(count_csv "")"         "count_csv empty"
+assert_eq 3 "$(count_csv "1,2,3")"    "count_csv three"
+
+# Restarts age out of the hour window
+assert_eq "5000,7000" "$(recent_restarts 8000 "1000,4399,5000,7000")" "recent_restarts drops >= 1h old"
+assert_eq ""          "$(recent_restarts 9000 "1000,5400")"           "recent_restarts all expired"
+
+T=10000
+while IFS='|' read -r now probe fails recent want; do
+  assert_eq "$want" "$(decide "$((T+now))" "$probe" "$fails" "$recent")" "decide t+${now} ok=${probe} fails=${fails} recent=${recent:-none}"
+done <<CASES
+0|1|5||ok
+0|0|1||wait
+0|0|2||wait
+0|0|3||restart
+10|0|3|$T|backoff
+29|0|3|$T|backoff
+30|0|3|$T|restart
+59|0|2|$T,$((T+30))|wait
+59|0|3|$((T-100)),$((T+30))|backoff
+90|0|3|$((T-100)),$((T+30))|restart
+0|0|3|$((T-300)),$((T-200)),$((T-100)),$((T-50))|capped
+0|1|3|$((T-300)),$((T-200)),$((T-100)),$((T-50))|ok
+CASES
+
+# Replay a wedged instance: one probe per 10s tick; restarts recorded as watchdog.sh does
+fails=0; restarts=""; log=""
+for tick in $(seq 0 10 3990); do
+  now=$((T + tick))
+  fails=$((fails+1))
+  restarts="$(recent_restarts "$now" "$restarts")"
+  v="$(decide "$now" 0 "$fails" "$restarts")"
+  [ "$v" = "restart" ] && { restarts="${restarts:+$restarts,}$now"; fails=0; log="${log:+$log,}$tick"; }
+done
+# 20s to reach the threshold, then gaps of >=30/60/120s; capped at 4 until the
+# first restart ages out (t=3620); later expiries shrink the backoff again
+assert_eq "20,50,110,230,3620,3710,3830,3950" "$log" "simulated hour: backoff doubles, cap holds, window expiry resumes"
+
+# Webhook: state changes only
+while IFS='|' read -r action prev fails want; do
+  rc=0; should_notify "$action" "$prev" "$fails" || rc=1
+  assert_eq "$want" "$rc" "should_notify ${action} after ${prev:-none} fails=${fails}"
+done <<'CASES'
+probe_failed||1|0
+probe_failed|probe_failed|2|1
+backoff|probe_failed|3|1
+restarted|backoff|0|0
+restart_failed|restart_failed|4|0
+capped|backoff|3|0
+capped|capped|4|1
+restart_dry_run|restart_dry_run|5|1
+recovered|capped|0|0
+skipped||0|1
+CASES
+
+# Replay a wedged instance with watchdog.sh's event names: only transitions notify
+fails=0; restarts=""; last=""; sent=""
+for tick in $(seq 0 10 1990); do
+  now=$((T + tick))
+  fails=$((fails+1))
+  restarts="$(recent_restarts "$now" "$restarts")"
+  v="$(decide "$now" 0 "$fails" "$restarts")"
+  case "$v" in wait) a=probe_failed;; restart) a=restarted; restarts="${restarts:+$restarts,}$now"; fails=0;; *) a="$v";; esac
+  should_notify "$a" "$last" "$fails" && sent="${sent:+$sent,}$a"
+  last="$a"
+done
+assert_eq "probe_failed,restarted,probe_failed,restarted,probe_failed,restarted,probe_failed,restarted,probe_failed,capped" \
+  "$sent" "wedged replay: no per-round backoff/capped/probe_failed notifications"
+
+finish
@@ -13,7 +13,7 @@ usage(){
   echo "Usage: $0 [@envfile.env] <stack> [command] [args...]"
   echo "Stacks: ollama | vLLM | llama.cpp | Triton"
   echo "Commands per stack:"
-  echo "  ollama   : benchmark (default) | install | service-cleanup | store-cleanup | cleanup-variants"
+  echo "  ollama   : benchmark (default) | install | service-cleanup | store-cleanup | store-verify | enforce-quota | usage-record | usage-report | cleanup-variants | variants-stale | bake-modelfile | bake-sweep | promote | rollback | promotions | watchdog | export-variant | import-variant | fixtures"
   echo "  vLLM     : benchmark (default) | install"
   echo "  llama.cpp: benchmark (default) | import-gguf | install"
   echo "  Triton   : benchmark (default) | install"
@@ -69,8 +69,22 @@ case "$stack" in
       install)                   exec "${STACK_ROOT}/ollama/install.sh" "$@" ;;
       service-cleanup|svc-clean) exec "${STACK_ROOT}/ollama/service-cleanup.sh" "$@" ;;
       store-cleanup|store)       exec "${STACK_ROOT}/ollama/store-cleanup.sh" "$@" ;;
+      store-verify|verify)       exec "${STACK_ROOT}/ollama/store-verify.sh" "$@" ;;
+      enforce-quota|quota)       exec "${STACK_ROOT}/ollama/store-quota.sh" "$@" ;;
+      usage-record)              exec "${STACK_ROOT}/ollama/store-usage.sh" record "$@" ;;
+      usage-report|usage)        exec "${STACK_ROOT}/ollama/store-usage.sh" report "$@" ;;
       export-gguf|export)        exec "${STACK_ROOT}/ollama/export-gguf.sh" "$@" ;;
       cleanup-variants|variants) exec "${STACK_ROOT}/ollama/cleanup-variants.sh" "$@" ;;
+      variants-stale|stale)      exec "${STACK_ROOT}/ollama/variants-stale.sh" "$@" ;;
+      bake-modelfile|modelfile)  exec "${STACK_ROOT}/ollama/bake-modelfile.sh" "$@" ;;
+      bake-sweep|sweep)          exec "${STACK_ROOT}/ollama/bake-sweep.sh" "$@" ;;
+      promote)                   exec "${STACK_ROOT}/ollama/promote-variant.sh" promote "$@" ;;
+      rollback)                  exec "${STACK_ROOT}/ollama/promote-variant.sh" rollback "$@" ;;
+      promotions)                exec "${STACK_ROOT}/ollama/promote-variant.sh" status "$@" ;;
+      watchdog)                  exec "${STACK_ROOT}/ollama/watchdog.sh" "$@" ;;
+      export-variant)            exec "${STACK_ROOT}/ollama/variant-bundle.sh" export "$@" ;;
+      import-variant)            exec "${STACK_ROOT}/ollama/variant-bundle.sh" import "$@" ;;
+      fixtures|fixtures-generate) exec "${STACK_ROOT}/ollama/store-fixtures.sh" "$@" ;;
       *) echo "Unknown ollama command: $cmd" >&2; usage; exit 2;;
     esac ;;
   vllm|vLLM|VLLM)